@@ -1,23 +1,463 @@
-use crate::config::Config;
+extern crate serde_json;
+
+use crate::config::{Config, Error as ConfigError, Formats, Locator, Problem};
+use crate::control::{Command, ControlServer, Request, StatusResponse};
+use crate::digest::{Digest, DigestJob, Schedule};
+use crate::history::{HistoryDatabase, Probe};
+use crate::locale::{DurationStyle, Locale};
 use crate::notifier::command_executor::CommandExecutor;
 use crate::notifier::ifttt_webhook::IFTTTWebhook;
 use crate::notifier::stdout_printer::StdoutPrinter;
 use crate::notifier::twitter_eggmode::TwitterEggMode;
 use crate::notifier::{Error as NotifierError, Message, NotifierStrategy};
-use crate::status_checker::{FormatError, Status, StatusChecker, StatusDifference, StatusFormats};
-use std::{thread, time};
+use crate::quiet_hours::{QuietAction, QuietHours, QuietRule, TimeWindow};
+use crate::signals::Signals;
+use crate::status_checker::{
+    Coalescer, CountRules, FormatError, FormatStrings, PlayerFilter, PlayerOverride,
+    PlayerRegistry, PollingInterval, ServerContext, StateFile, Status, StatusChecker,
+    StatusDifference, StatusFormats, Thresholds,
+};
+use crate::timezone::Timezone;
+use chrono::{DateTime, Utc};
+use std::path::{Path, PathBuf};
+use std::time;
+
+/// (sec) the saved status older than this is ignored by default.
+const DEFAULT_STATE_MAX_AGE: u64 = 600;
+
+/// (day) the default of `[history] retention_days`.
+const DEFAULT_RETENTION_DAYS: u32 = 90;
+
+/// the default of `[player_count] hysteresis`.
+const DEFAULT_HYSTERESIS: u32 = 1;
+
+/// how often signals are looked at while waiting for control commands.
+const WAIT_STEP: time::Duration = time::Duration::from_millis(100);
 
 pub struct Application {
     config: Config,
+    config_path: PathBuf,
+}
+
+/// a notifier and the formats of the messages sent to it.
+struct Channel {
+    notifier: Box<dyn NotifierStrategy>,
+    formats: StatusFormats,
+}
+
+/// everything built from the configuration, which is rebuilt on reload.
+struct Monitor {
+    channels: Vec<Channel>,
+    player_filter: PlayerFilter,
+    coalescer: Coalescer,
+    status_checker: StatusChecker,
+    polling_interval: PollingInterval,
+    state_file: Option<StateFile>,
+    history: Option<HistoryDatabase>,
+    digest_jobs: Vec<DigestJob>,
+    quiet_hours: QuietHours,
+    control: Option<ControlServer>,
+    /// maintenance mode: the status is still checked but nothing is sent.
+    paused: bool,
+    resume_at: Option<DateTime<Utc>>,
 }
 
 impl Application {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    pub fn new(config: Config, config_path: &Path) -> Self {
+        Self {
+            config,
+            config_path: config_path.to_path_buf(),
+        }
+    }
+
+    pub fn run(&mut self) {
+        let signals = match Signals::register() {
+            Ok(signals) => signals,
+            Err(e) => {
+                error!("Couldn't register the signal handlers: {:?}", e);
+                return;
+            }
+        };
+
+        let channels = match self.channels() {
+            Ok(channels) => channels,
+            Err(FormatError::FormatError(reason)) => {
+                error!("Invalid message format: {}", reason);
+                return;
+            }
+        };
+        if channels.is_empty() {
+            error!("No strategies available!");
+            return;
+        }
+
+        let history = self.open_history();
+        let digest_jobs = self.digest_jobs();
+        if !digest_jobs.is_empty() && history.is_none() {
+            warn!("Digests need [history] to be enabled.");
+        }
+
+        let state_file = self.state_file();
+        let mut monitor = Monitor {
+            channels,
+            player_filter: self.player_filter(),
+            coalescer: self.coalescer(),
+            status_checker: self.status_checker(state_file.as_ref()),
+            polling_interval: self.polling_interval(),
+            state_file,
+            history,
+            digest_jobs,
+            quiet_hours: self.quiet_hours(),
+            control: self.control_server(),
+            paused: false,
+            resume_at: None,
+        };
+
+        info!("Start checking.");
+
+        while !signals.terminate_requested() {
+            if signals.take_reload() {
+                self.reload(&mut monitor);
+            }
+
+            if monitor
+                .resume_at
+                .map_or(false, |resume_at| resume_at <= Utc::now())
+            {
+                info!("Resumed notifications.");
+                monitor.paused = false;
+                monitor.resume_at = None;
+            }
+
+            let channels: &[Channel] = if monitor.paused {
+                &[]
+            } else {
+                &monitor.channels
+            };
+
+            let changed = Self::check_and_notify(
+                &mut monitor.status_checker,
+                &monitor.player_filter,
+                &mut monitor.coalescer,
+                channels,
+                &mut monitor.quiet_hours,
+            );
+
+            Self::save_state(&monitor);
+
+            if let Some(ref mut history) = monitor.history {
+                self.record_history(history, &monitor.status_checker);
+                Self::send_digests(
+                    &mut monitor.digest_jobs,
+                    history,
+                    &monitor.status_checker.server_context(),
+                    channels,
+                    &mut monitor.quiet_hours,
+                );
+            }
+
+            // the held messages are kept until the notifications are resumed
+            if !monitor.paused {
+                let held = monitor.quiet_hours.release(Utc::now());
+                Self::post_held(held, &monitor.channels);
+            }
+
+            let mut interval = monitor
+                .polling_interval
+                .next(monitor.status_checker.latest_status(), changed);
+            // wake up when the coalescing window closes
+            if let Some(remaining) = monitor.coalescer.remaining(Utc::now()) {
+                interval = interval.min(remaining.to_std().unwrap_or_default());
+            }
+            debug!("Next check in {:?}", interval);
+            Self::wait(&mut monitor, &signals, interval);
+        }
+
+        Self::stop(&mut monitor);
     }
 
-    pub fn run(&self) {
-        let mut notifier_strategies: Vec<Box<dyn NotifierStrategy>> = Vec::new();
+    /// waits for `interval` while answering control commands.
+    /// returns early on signals or `check`.
+    fn wait(monitor: &mut Monitor, signals: &Signals, interval: time::Duration) {
+        if monitor.control.is_none() {
+            return signals.sleep(interval);
+        }
+
+        let until = time::Instant::now() + interval;
+        while !signals.pending() {
+            let now = time::Instant::now();
+            if now >= until {
+                return;
+            }
+
+            let request = match monitor.control {
+                Some(ref control) => control.recv_timeout(WAIT_STEP.min(until - now)),
+                None => None,
+            };
+            if let Some(request) = request {
+                if Self::handle_request(monitor, &request) {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// returns true if the status should be checked right now.
+    fn handle_request(monitor: &mut Monitor, request: &Request) -> bool {
+        match request.command {
+            Command::Pause(period) => {
+                monitor.paused = true;
+                // a period beyond the range of time pauses until resumed
+                monitor.resume_at = period.and_then(|period| Utc::now().checked_add_signed(period));
+                match monitor.resume_at {
+                    Some(resume_at) => info!("Paused notifications until {}.", resume_at),
+                    None => info!("Paused notifications."),
+                }
+                request.respond("ok");
+            }
+            Command::Resume => {
+                monitor.paused = false;
+                monitor.resume_at = None;
+                info!("Resumed notifications.");
+                request.respond("ok");
+            }
+            Command::Check => {
+                request.respond("ok");
+                return true;
+            }
+            Command::Status => {
+                let response = StatusResponse {
+                    paused: monitor.paused,
+                    resume_at: monitor.resume_at,
+                    status: monitor.status_checker.latest_status(),
+                };
+                match serde_json::to_string(&response) {
+                    Ok(json) => request.respond(&json),
+                    Err(e) => request.respond(&format!("error: {}", e)),
+                }
+            }
+            Command::Announce(ref message) => {
+                let message = Message::new(message)
+                    .with_event("announce")
+                    .with_server(&monitor.status_checker.server_context());
+                for channel in monitor.channels.iter() {
+                    Self::post(channel.notifier.as_ref(), &message);
+                }
+                request.respond("ok");
+            }
+        }
+
+        false
+    }
+
+    /// sends the stopped message and the held messages, which would be lost otherwise,
+    /// unless the notifications are paused.
+    fn stop(monitor: &mut Monitor) {
+        info!("Stop checking.");
+        Self::save_state(monitor);
+
+        if monitor.paused {
+            return;
+        }
+
+        let server = monitor.status_checker.server_context();
+        for status_difference in monitor.coalescer.flush() {
+            Self::notify(
+                &status_difference,
+                &server,
+                &monitor.channels,
+                &mut monitor.quiet_hours,
+            );
+        }
+        monitor
+            .status_checker
+            .forget_new_players(&monitor.coalescer.take_unwelcomed());
+
+        let now = Utc::now();
+        for channel in monitor.channels.iter() {
+            match channel.formats.format_stopped(&server) {
+                Ok(Some(body)) => {
+                    let message = Message::new(&body)
+                        .with_event("stopped")
+                        .with_server(&server);
+                    Self::send(channel, &message, &mut monitor.quiet_hours, now)
+                }
+                Ok(None) => {}
+                Err(FormatError::FormatError(reason)) => {
+                    error!("Error occurred while formatting a message: {}", reason)
+                }
+            }
+        }
+
+        let held = monitor.quiet_hours.take_all();
+        Self::post_held(held, &monitor.channels);
+    }
+
+    /// re-reads the configuration and rebuilds what the changed sections affect.
+    /// the checker keeps the current status as long as the server address is the same.
+    fn reload(&mut self, monitor: &mut Monitor) {
+        let config = match Config::read_path(&self.config_path) {
+            Ok(config) => config,
+            Err(ConfigError::InvalidConfig(problems)) => {
+                for problem in problems {
+                    error!("Couldn't reload the configuration: {}", problem);
+                }
+                return;
+            }
+            Err(e) => {
+                error!("Couldn't reload the configuration: {:?}", e);
+                return;
+            }
+        };
+
+        let changed = self.config.changed_sections(&config);
+        if changed.is_empty() {
+            info!("Reloaded the configuration: nothing changed.");
+            return;
+        }
+        info!(
+            "Reloaded the configuration: {} changed.",
+            changed.join(", ")
+        );
+
+        let previous = std::mem::replace(&mut self.config, config);
+        let changed_any = |sections: &[&str]| sections.iter().any(|s| changed.contains(s));
+
+        // `timezone` in `[mcnotify]` is the default of the formats
+        if changed_any(&[
+            "formats", "twitter", "ifttt", "command", "stdout", "mcnotify",
+        ]) {
+            match self.channels() {
+                Ok(channels) if channels.is_empty() => {
+                    error!("No strategies available! The previous notifiers are kept.")
+                }
+                Ok(channels) => monitor.channels = channels,
+                Err(FormatError::FormatError(reason)) => {
+                    error!(
+                        "Invalid message format: {}. The previous notifiers and formats are kept.",
+                        reason
+                    )
+                }
+            }
+        }
+
+        if changed_any(&["player_filter"]) {
+            monitor.player_filter = self.player_filter();
+        }
+
+        if changed_any(&["mcnotify"]) {
+            // the changes held in the previous window are sent right away
+            let channels: &[Channel] = if monitor.paused {
+                &[]
+            } else {
+                &monitor.channels
+            };
+            for status_difference in monitor.coalescer.flush() {
+                Self::notify(
+                    &status_difference,
+                    &monitor.status_checker.server_context(),
+                    channels,
+                    &mut monitor.quiet_hours,
+                );
+            }
+            monitor
+                .status_checker
+                .forget_new_players(&monitor.coalescer.take_unwelcomed());
+            monitor.coalescer = self.coalescer();
+        }
+
+        if changed_any(&["mcnotify", "address", "player_count"]) {
+            let state = monitor.status_checker.state(chrono::Utc::now());
+            monitor.state_file = self.state_file();
+            monitor.status_checker = self.status_checker(monitor.state_file.as_ref());
+            if previous.address == self.config.address {
+                monitor.status_checker.restore(state);
+            }
+            monitor.polling_interval = self.polling_interval();
+        }
+
+        if changed_any(&["address", "history"]) {
+            monitor.history = self.open_history();
+        }
+
+        if changed_any(&["mcnotify", "digest"]) {
+            monitor.digest_jobs = self.digest_jobs();
+        }
+
+        if changed_any(&["mcnotify", "quiet_hours"]) {
+            // the windows holding the messages may not exist anymore
+            let held = monitor.quiet_hours.take_all();
+            if !monitor.paused {
+                Self::post_held(held, &monitor.channels);
+            }
+            monitor.quiet_hours = self.quiet_hours();
+        }
+
+        if changed_any(&["address", "control"]) {
+            // the previous socket has to be closed before binding the same path
+            monitor.control = None;
+            monitor.control = self.control_server();
+        }
+
+        if !monitor.digest_jobs.is_empty() && monitor.history.is_none() {
+            warn!("Digests need [history] to be enabled.");
+        }
+    }
+
+    pub fn control_socket_path(&self) -> Option<PathBuf> {
+        let conf = self.config.control.as_ref()?;
+
+        if let Some(ref path) = conf.socket {
+            return Some(PathBuf::from(path));
+        }
+
+        let address = &self.config.address;
+        match ControlServer::default_path(&address.hostname, address.port) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("The control socket is not available: {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn control_server(&self) -> Option<ControlServer> {
+        let path = self.control_socket_path()?;
+
+        match ControlServer::bind(&path) {
+            Ok(control) => {
+                info!("Listening for control commands on {:?}", control.path());
+                Some(control)
+            }
+            Err(e) => {
+                warn!("Couldn't open the control socket: {:?}", e);
+                None
+            }
+        }
+    }
+
+    /// the notifiers with the formats in their sections applied.
+    fn channels(&self) -> Result<Vec<Channel>, FormatError> {
+        // the errors in `[formats]` are reported as they are, not as the ones of a notifier
+        let timezone = self.timezone();
+        Self::status_formats(&self.config.formats, timezone)?;
+
+        self.notifier_strategies()
+            .into_iter()
+            .map(|(notifier, overrides)| {
+                let formats = self.config.formats.with_overrides(overrides);
+                match Self::status_formats(&formats, timezone) {
+                    Ok(formats) => Ok(Channel { notifier, formats }),
+                    Err(FormatError::FormatError(reason)) => Err(FormatError::FormatError(
+                        format!("[{}.formats] {}", notifier.name(), reason),
+                    )),
+                }
+            })
+            .collect()
+    }
+
+    fn notifier_strategies(&self) -> Vec<(Box<dyn NotifierStrategy>, &Formats)> {
+        let mut notifier_strategies: Vec<(Box<dyn NotifierStrategy>, &Formats)> = Vec::new();
 
         if let Some(conf) = &self.config.twitter {
             let strategy = TwitterEggMode::new(
@@ -26,62 +466,440 @@ impl Application {
                 &conf.access_key,
                 &conf.access_secret,
             );
-            notifier_strategies.push(Box::new(strategy));
+            notifier_strategies.push((Box::new(strategy), &conf.formats));
         }
 
         if let Some(conf) = &self.config.ifttt {
-            let strategy = IFTTTWebhook::new(&conf.endpoint_url, conf.truncate);
-            notifier_strategies.push(Box::new(strategy));
+            let strategy = IFTTTWebhook::new(&conf.endpoint_url, conf.max_length);
+            notifier_strategies.push((Box::new(strategy), &conf.formats));
         }
 
         if let Some(conf) = &self.config.command {
-            let strategy = CommandExecutor::new(&conf.command, conf.args.clone(), conf.pipe);
-            notifier_strategies.push(Box::new(strategy));
+            let strategy = CommandExecutor::new(&conf.command, conf.args.clone(), conf.pipe)
+                .with_json(conf.json);
+            notifier_strategies.push((Box::new(strategy), &conf.formats));
         }
 
-        if self.config.stdout.is_some() {
+        if let Some(conf) = &self.config.stdout {
             let strategy = StdoutPrinter::default();
-            notifier_strategies.push(Box::new(strategy));
+            notifier_strategies.push((Box::new(strategy), &conf.formats));
         }
 
-        if notifier_strategies.is_empty() {
-            error!("No strategies available!");
-            return;
+        notifier_strategies
+    }
+
+    /// every problem in `[formats]` and the formats of each notifier, with the lines where they are.
+    /// the ones inherited from `[formats]` are reported only there.
+    pub fn format_problems(config: &Config, locator: &Locator) -> Vec<Problem> {
+        config
+            .formats_tables()
+            .into_iter()
+            .flat_map(|(table, formats)| {
+                let formats = match table {
+                    "formats" => formats.clone(),
+                    _ => config.formats.with_overrides(formats),
+                };
+                Self::check_formats(table, &formats, locator)
+            })
+            .collect()
+    }
+
+    fn check_formats(table: &str, formats: &Formats, locator: &Locator) -> Vec<Problem> {
+        let problem = |key: &str, reason: String| {
+            let line = locator.key(table, key);
+            if line.is_none() && table != "formats" {
+                return None;
+            }
+            Some(Problem::new(line, format!("[{}] {}", table, reason)))
+        };
+
+        // the invalid settings are reported by `Config::check`,
+        // and the defaults are used to check the templates
+        let invalid = |value: &Option<String>, parse: fn(&str) -> bool| {
+            value.as_deref().map_or(false, |value| !parse(value))
+        };
+        let mut valid = formats.clone();
+        if invalid(&formats.locale, |s| s.parse::<Locale>().is_ok()) {
+            valid.locale = None;
+        }
+        if invalid(&formats.timezone, |s| s.parse::<Timezone>().is_ok()) {
+            valid.timezone = None;
+        }
+        if invalid(&formats.duration_style, |s| {
+            s.parse::<DurationStyle>().is_ok()
+        }) {
+            valid.duration_style = None;
         }
 
-        let config_formats = &self.config.formats;
-        let status_formats = StatusFormats {
-            recover_msg: config_formats.recover_msg.clone(),
-            down_msg: config_formats.down_msg.clone(),
-            join_fmt: config_formats.join_fmt.clone(),
-            leave_fmt: config_formats.leave_fmt.clone(),
-            players_fmt: config_formats.players_fmt.clone(),
-            time_fmt: config_formats.time_fmt.clone(),
+        match Self::format_strings(&valid, Timezone::Local) {
+            Ok(strings) => StatusFormats::check(&strings)
+                .into_iter()
+                .filter_map(|(name, FormatError::FormatError(reason))| problem(name, reason))
+                .collect(),
+            Err(_) => Vec::new(),
+        }
+    }
+
+    /// `timezone` is used unless the formats set one.
+    fn status_formats(
+        config_formats: &Formats,
+        timezone: Timezone,
+    ) -> Result<StatusFormats, FormatError> {
+        StatusFormats::new(&Self::format_strings(config_formats, timezone)?)
+    }
+
+    /// the formats which are not set are taken from the bundle of the locale.
+    fn format_strings(
+        config_formats: &Formats,
+        timezone: Timezone,
+    ) -> Result<FormatStrings, FormatError> {
+        let locale = match config_formats.locale {
+            Some(ref locale) => locale.parse().map_err(FormatError::FormatError)?,
+            None => Locale::default(),
+        };
+        let timezone = match config_formats.timezone {
+            Some(ref timezone) => timezone.parse().map_err(FormatError::FormatError)?,
+            None => timezone,
+        };
+        let duration_style = match config_formats.duration_style {
+            Some(ref style) => style.parse().map_err(FormatError::FormatError)?,
+            None => DurationStyle::default(),
+        };
+        let format = |value: &Option<String>, name: &str| {
+            value
+                .clone()
+                .unwrap_or_else(|| locale.default_format(name).to_owned())
         };
 
-        let interval = time::Duration::from_secs(self.config.mcnotify.check_interval as u64);
-        let mut status_checker =
-            StatusChecker::new(&self.config.address.hostname, self.config.address.port);
+        Ok(FormatStrings {
+            recover_msg: format(&config_formats.recover_msg, "recover_msg"),
+            down_msg: format(&config_formats.down_msg, "down_msg"),
+            join_fmt: format(&config_formats.join_fmt, "join_fmt"),
+            leave_fmt: format(&config_formats.leave_fmt, "leave_fmt"),
+            join_count_fmt: format(&config_formats.join_count_fmt, "join_count_fmt"),
+            leave_count_fmt: format(&config_formats.leave_count_fmt, "leave_count_fmt"),
+            motd_change_fmt: format(&config_formats.motd_change_fmt, "motd_change_fmt"),
+            version_change_fmt: format(&config_formats.version_change_fmt, "version_change_fmt"),
+            max_players_change_fmt: format(
+                &config_formats.max_players_change_fmt,
+                "max_players_change_fmt",
+            ),
+            count_reached_fmt: format(&config_formats.count_reached_fmt, "count_reached_fmt"),
+            full_fmt: format(&config_formats.full_fmt, "full_fmt"),
+            empty_msg: format(&config_formats.empty_msg, "empty_msg"),
+            new_player_fmt: format(&config_formats.new_player_fmt, "new_player_fmt"),
+            digest_fmt: format(&config_formats.digest_fmt, "digest_fmt"),
+            stopped_msg: format(&config_formats.stopped_msg, "stopped_msg"),
+            players_fmt: format(&config_formats.players_fmt, "players_fmt"),
+            time_fmt: format(&config_formats.time_fmt, "time_fmt"),
+            locale,
+            timezone,
+            duration_style,
+        })
+    }
 
-        info!("Start checking.");
+    fn player_filter(&self) -> PlayerFilter {
+        match self.config.player_filter {
+            Some(ref conf) => {
+                let overrides = conf
+                    .overrides
+                    .iter()
+                    .map(|o| PlayerOverride {
+                        player: o.player.clone(),
+                        announce: o.announce,
+                        display_name: o.display_name.clone(),
+                    })
+                    .collect();
+                PlayerFilter::new(conf.only.clone(), conf.ignore.clone(), overrides)
+            }
+            None => PlayerFilter::default(),
+        }
+    }
+
+    fn coalescer(&self) -> Coalescer {
+        let window = self.config.mcnotify.coalesce_window.unwrap_or(0);
+        Coalescer::new(chrono::Duration::seconds(window as i64))
+    }
+
+    fn polling_interval(&self) -> PollingInterval {
+        let mcnotify = &self.config.mcnotify;
+        let check_interval = mcnotify.check_interval as u64;
+
+        PollingInterval::new(
+            time::Duration::from_secs(check_interval),
+            time::Duration::from_secs(mcnotify.min_check_interval.unwrap_or(check_interval)),
+            time::Duration::from_secs(mcnotify.max_check_interval.unwrap_or(check_interval)),
+        )
+    }
+
+    /// builds a checker, restoring the status saved in `state_file`.
+    fn status_checker(&self, state_file: Option<&StateFile>) -> StatusChecker {
+        let mcnotify = &self.config.mcnotify;
+        let default_thresholds = Thresholds::default();
+        let thresholds = Thresholds {
+            down: mcnotify.down_threshold.unwrap_or(default_thresholds.down),
+            recover: mcnotify
+                .recover_threshold
+                .unwrap_or(default_thresholds.recover),
+            grace_period: mcnotify
+                .down_grace_period
+                .map(time::Duration::from_secs)
+                .unwrap_or(default_thresholds.grace_period),
+        };
+
+        let mut status_checker = StatusChecker::new(
+            &self.config.address.hostname,
+            self.config.address.port,
+            thresholds,
+        );
+
+        if let Some(ref conf) = self.config.player_count {
+            let rules = CountRules {
+                thresholds: conf.thresholds.clone(),
+                full: conf.full,
+                empty: conf.empty,
+                hysteresis: conf.hysteresis.unwrap_or(DEFAULT_HYSTERESIS),
+            };
+            status_checker = status_checker.with_count_rules(rules);
+        }
+
+        if let Some(grace) = mcnotify.rejoin_grace_period {
+            status_checker =
+                status_checker.with_rejoin_grace(chrono::Duration::seconds(grace as i64));
+        }
+
+        if let Some(path) = self.player_registry_path() {
+            match PlayerRegistry::open(&path) {
+                Ok(registry) => status_checker = status_checker.with_registry(registry),
+                Err(e) => warn!("Couldn't open the player registry: {:?}", e),
+            }
+        }
+
+        if let Some(state_file) = state_file {
+            let max_age =
+                time::Duration::from_secs(mcnotify.state_max_age.unwrap_or(DEFAULT_STATE_MAX_AGE));
+            match state_file.load(max_age, chrono::Utc::now()) {
+                Ok(Some(state)) => {
+                    info!("Restored the status from {:?}", state_file.path());
+                    status_checker.restore(state);
+                }
+                Ok(None) => {}
+                Err(e) => warn!("Couldn't restore the status: {:?}", e),
+            }
+        }
+
+        status_checker
+    }
+
+    fn save_state(monitor: &Monitor) {
+        if let Some(ref state_file) = monitor.state_file {
+            let state = monitor.status_checker.state(chrono::Utc::now());
+            if let Err(e) = state_file.save(&state) {
+                warn!("Couldn't save the status: {:?}", e);
+            }
+        }
+    }
+
+    pub fn history_path(&self) -> Option<PathBuf> {
+        let conf = self.config.history.as_ref()?;
+
+        if let Some(ref path) = conf.path {
+            return Some(PathBuf::from(path));
+        }
+
+        match HistoryDatabase::default_path() {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("History will not be recorded: {:?}", e);
+                None
+            }
+        }
+    }
+
+    pub fn open_history(&self) -> Option<HistoryDatabase> {
+        let path = self.history_path()?;
+        let address = &self.config.address;
+
+        match HistoryDatabase::open(&path, &address.hostname, address.port) {
+            Ok(history) => Some(history),
+            Err(e) => {
+                warn!("Couldn't open the history database: {:?}", e);
+                None
+            }
+        }
+    }
 
-        loop {
-            Self::check_and_notify(&mut status_checker, &status_formats, &notifier_strategies);
-            thread::sleep(interval);
+    fn record_history(&self, history: &mut HistoryDatabase, status_checker: &StatusChecker) {
+        let now = chrono::Utc::now();
+        let probe = Probe::from_status(status_checker.latest_status(), now);
+
+        if let Err(e) = history.record(&probe) {
+            warn!("Couldn't record the status: {:?}", e);
+        }
+
+        let retention_days = self
+            .config
+            .history
+            .as_ref()
+            .and_then(|conf| conf.retention_days)
+            .unwrap_or(DEFAULT_RETENTION_DAYS);
+        if let Err(e) = history.apply_retention(chrono::Duration::days(retention_days as i64), now)
+        {
+            warn!("Couldn't remove old records: {:?}", e);
+        }
+    }
+
+    fn timezone(&self) -> Timezone {
+        match self.config.mcnotify.timezone {
+            Some(ref timezone) => timezone.parse().expect("checked by Config::check"),
+            None => Timezone::Local,
+        }
+    }
+
+    fn quiet_hours(&self) -> QuietHours {
+        let rules = self
+            .config
+            .quiet_hours
+            .iter()
+            .map(|conf| QuietRule {
+                window: TimeWindow::new(&conf.start, &conf.end, &conf.days)
+                    .expect("checked by Config::check"),
+                events: conf.events.clone(),
+                notifiers: conf.notifiers.clone(),
+                action: conf.action.as_ref().map_or(QuietAction::Hold, |action| {
+                    action.parse().expect("checked by Config::check")
+                }),
+            })
+            .collect();
+
+        QuietHours::new(rules, self.timezone())
+    }
+
+    fn digest_jobs(&self) -> Vec<DigestJob> {
+        let now = chrono::Utc::now();
+        let timezone = self.timezone();
+
+        self.config
+            .digests
+            .iter()
+            .map(|conf| {
+                let schedule: Schedule = conf.schedule.parse().expect("checked by Config::check");
+                DigestJob::new(schedule, timezone, now)
+            })
+            .collect()
+    }
+
+    fn send_digests(
+        digest_jobs: &mut [DigestJob],
+        history: &HistoryDatabase,
+        server: &ServerContext,
+        channels: &[Channel],
+        quiet_hours: &mut QuietHours,
+    ) {
+        let now = chrono::Utc::now();
+
+        for job in digest_jobs.iter_mut() {
+            let (since, until) = match job.poll(now) {
+                Some(period) => period,
+                None => continue,
+            };
+
+            let digest = match history.probes(since) {
+                Ok(probes) => Digest::new(since, until, &probes),
+                Err(e) => {
+                    error!("Couldn't read the status history: {:?}", e);
+                    continue;
+                }
+            };
+
+            for channel in channels.iter() {
+                match channel.formats.format_digest(&digest, server) {
+                    Ok(body) => {
+                        let message = Message::new(&body)
+                            .with_event("digest")
+                            .with_server(server)
+                            .with_joined_players(&digest.players);
+                        Self::send(channel, &message, quiet_hours, now)
+                    }
+                    Err(FormatError::FormatError(reason)) => {
+                        error!("Error occurred while formatting a digest: {}", reason)
+                    }
+                }
+            }
+        }
+    }
+
+    pub fn player_registry_path(&self) -> Option<PathBuf> {
+        if let Some(ref path) = self.config.mcnotify.player_registry {
+            return Some(PathBuf::from(path));
+        }
+
+        let address = &self.config.address;
+        match PlayerRegistry::default_path(&address.hostname, address.port) {
+            Ok(path) => Some(path),
+            Err(e) => {
+                warn!("Players will not be recorded: {:?}", e);
+                None
+            }
+        }
+    }
+
+    fn state_file(&self) -> Option<StateFile> {
+        if let Some(ref path) = self.config.mcnotify.state_file {
+            return Some(StateFile::new(Path::new(path)));
+        }
+
+        let address = &self.config.address;
+        match StateFile::default_path(&address.hostname, address.port) {
+            Ok(path) => Some(StateFile::new(&path)),
+            Err(e) => {
+                warn!("Status will not be saved: {:?}", e);
+                None
+            }
         }
     }
 
     fn check_and_notify(
         status_checker: &mut StatusChecker,
-        status_formats: &StatusFormats,
-        notifier_strategies: &[Box<dyn NotifierStrategy>],
-    ) {
-        let status_difference = status_checker.get_status_difference();
+        player_filter: &PlayerFilter,
+        coalescer: &mut Coalescer,
+        channels: &[Channel],
+        quiet_hours: &mut QuietHours,
+    ) -> bool {
+        let mut changed = false;
+
+        let status_differences = status_checker.get_status_differences();
+        let server = status_checker.server_context();
+        let now = Utc::now();
+
+        for status_difference in status_differences {
+            changed |= !matches!(status_difference, StatusDifference::None { .. });
+
+            if let Some(status_difference) = player_filter.apply(status_difference) {
+                for status_difference in coalescer.push(status_difference, now) {
+                    Self::notify(&status_difference, &server, channels, quiet_hours);
+                }
+            }
+        }
 
-        match status_difference {
-            StatusDifference::Down { ref reason } => {
+        for status_difference in coalescer.poll(now) {
+            Self::notify(&status_difference, &server, channels, quiet_hours);
+        }
+        status_checker.forget_new_players(&coalescer.take_unwelcomed());
+
+        changed
+    }
+
+    /// formats the difference for each notifier, so every notifier gets its own message.
+    fn notify(
+        status_difference: &StatusDifference,
+        server: &ServerContext,
+        channels: &[Channel],
+        quiet_hours: &mut QuietHours,
+    ) {
+        match *status_difference {
+            StatusDifference::Down { ref reason, .. } => {
                 error!("Server is down: {}", reason);
-                return;
             }
             StatusDifference::None {
                 latest_status: Status::Unavailable { ref reason },
@@ -92,26 +910,55 @@ impl Application {
             _ => {}
         }
 
-        let message_result = status_formats.format(&status_difference);
-        let message_opt = match message_result {
-            Ok(message) => message,
-            Err(FormatError::FormatError(reason)) => {
-                error!("Error occurred while formatting a status: {}", reason);
-                return;
-            }
-        };
-
-        let message = match message_opt {
-            Some(message) => message,
-            None => return,
-        };
+        let now = chrono::Utc::now();
 
-        for notifier in notifier_strategies.iter() {
-            match notifier.notify(&Message::new(&message)) {
-                Ok(()) => {}
-                Err(NotifierError::FailedToPostMessage(ref msg)) => {
-                    error!("Failed to notify. {:?}", msg)
+        for channel in channels.iter() {
+            let body = match channel.formats.format(status_difference, server) {
+                Ok(Some(body)) => body,
+                Ok(None) => continue,
+                Err(FormatError::FormatError(reason)) => {
+                    error!("Error occurred while formatting a status: {}", reason);
+                    continue;
                 }
+            };
+
+            let message = status_difference.to_message(&body, server);
+            Self::send(channel, &message, quiet_hours, now);
+        }
+    }
+
+    /// posts the message unless quiet hours hold or drop it.
+    fn send(
+        channel: &Channel,
+        message: &Message,
+        quiet_hours: &mut QuietHours,
+        now: DateTime<Utc>,
+    ) {
+        let notifier = channel.notifier.as_ref();
+        if quiet_hours
+            .filter(notifier.name(), message.event(), message.body(), now)
+            .is_some()
+        {
+            Self::post(notifier, message);
+        }
+    }
+
+    /// sends the messages held during quiet hours to each notifier.
+    /// they are batched into a message, so only the text is kept.
+    fn post_held(held: Vec<(String, String)>, channels: &[Channel]) {
+        for (name, body) in held {
+            let message = Message::new(&body).with_event("held");
+            for channel in channels.iter().filter(|c| c.notifier.name() == name) {
+                Self::post(channel.notifier.as_ref(), &message);
+            }
+        }
+    }
+
+    fn post(notifier: &dyn NotifierStrategy, message: &Message) {
+        match notifier.notify(message) {
+            Ok(()) => {}
+            Err(NotifierError::FailedToPostMessage(ref msg)) => {
+                error!("Failed to notify. {:?}", msg)
             }
         }
     }
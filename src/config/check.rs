@@ -0,0 +1,578 @@
+extern crate reqwest;
+extern crate toml;
+
+use super::{Config, Formats};
+use crate::digest::Schedule;
+use crate::locale::{DurationStyle, Locale};
+use crate::quiet_hours::{QuietAction, TimeWindow};
+use crate::timezone::Timezone;
+use std::env;
+use std::fmt;
+use std::path::Path;
+
+/// a problem in the configuration, at the line where it is written if known.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Problem {
+    /// 1-based
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+impl Problem {
+    pub fn new(line: Option<usize>, message: String) -> Self {
+        Self { line, message }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+/// finds the lines of the tables and the keys in a TOML source.
+/// inline tables and dotted keys are not followed.
+pub struct Locator {
+    lines: Vec<Line>,
+}
+
+enum Line {
+    /// `[name]` or `[[name]]`
+    Header(String),
+    Key(String),
+    /// the lines of a multi-line value after the one of its key
+    Value,
+    /// comments and blank lines
+    Other,
+}
+
+impl Locator {
+    pub fn new(source: &str) -> Self {
+        let mut multiline: Option<&str> = None;
+
+        let lines = source
+            .lines()
+            .map(|text| {
+                let line = match multiline {
+                    Some(_) => Line::Value,
+                    None => Self::parse_line(text),
+                };
+
+                // a line with an odd number of delimiters opens or closes a multi-line string
+                for delimiter in ["\"\"\"", "'''"] {
+                    if multiline.map_or(true, |open| open == delimiter)
+                        && text.matches(delimiter).count() % 2 == 1
+                    {
+                        multiline = match multiline {
+                            Some(_) => None,
+                            None => Some(delimiter),
+                        };
+                    }
+                }
+                line
+            })
+            .collect();
+
+        Self { lines }
+    }
+
+    /// the 1-based line of the first `[table]` (or `[[table]]`).
+    pub fn table(&self, table: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| matches!(line, Line::Header(name) if name == table))
+            .map(|index| index + 1)
+    }
+
+    /// the 1-based line of `key` in `[table]`, or at the top level if `table` is empty.
+    pub fn key(&self, table: &str, key: &str) -> Option<usize> {
+        self.starts_of(table)
+            .into_iter()
+            .find_map(|start| self.key_from(start, key))
+            .map(|index| index + 1)
+    }
+
+    /// the 1-based line of `key` in the `nth` (0-based) `[[table]]`,
+    /// or of its header if the key is not written.
+    pub fn element_key(&self, table: &str, nth: usize, key: &str) -> Option<usize> {
+        let start = *self.starts_of(table).get(nth)?;
+        Some(self.key_from(start, key).unwrap_or(start - 1) + 1)
+    }
+
+    /// the indices of the first lines of the sections of `table`.
+    fn starts_of(&self, table: &str) -> Vec<usize> {
+        if table.is_empty() {
+            return vec![0];
+        }
+
+        self.lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| matches!(line, Line::Header(name) if name == table))
+            .map(|(index, _)| index + 1)
+            .collect()
+    }
+
+    /// the index of `key` in the section which starts at `start`.
+    fn key_from(&self, start: usize, key: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take_while(|(_, line)| !matches!(line, Line::Header(_)))
+            .find(|(_, line)| matches!(line, Line::Key(name) if name == key))
+            .map(|(index, _)| index)
+    }
+
+    /// the index after the last line of the value of the key at `index`.
+    fn value_end(&self, index: usize) -> usize {
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(index + 1)
+            .find(|(_, line)| !matches!(line, Line::Value))
+            .map_or(self.lines.len(), |(index, _)| index)
+    }
+
+    /// the index where the section which starts at `start` ends.
+    fn end_of(&self, start: usize) -> usize {
+        self.lines
+            .iter()
+            .enumerate()
+            .skip(start)
+            .find(|(_, line)| matches!(line, Line::Header(_)))
+            .map_or(self.lines.len(), |(index, _)| index)
+    }
+
+    fn parse_line(text: &str) -> Line {
+        let text = text.trim();
+
+        if text.starts_with('[') {
+            let name: String = text
+                .trim_start_matches('[')
+                .split(']')
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .filter(|c| !c.is_whitespace() && *c != '"' && *c != '\'')
+                .collect();
+            return Line::Header(name);
+        }
+
+        if text.starts_with('#') {
+            return Line::Other;
+        }
+
+        match text.split_once('=') {
+            Some((key, _)) => Line::Key(
+                key.trim()
+                    .trim_matches(|c| c == '"' || c == '\'')
+                    .to_owned(),
+            ),
+            None => Line::Other,
+        }
+    }
+}
+
+impl Config {
+    /// parses the source and returns every problem found, with the lines where they are.
+    /// the configuration is `None` if the source couldn't be read as a configuration.
+    pub fn check(source: &str) -> (Option<Config>, Vec<Problem>) {
+        let locator = Locator::new(source);
+        let (config, mut problems) = parse(source, &locator);
+
+        if let Some(ref config) = config {
+            problems.extend(config.problems(&locator));
+        }
+
+        (config, problems)
+    }
+
+    /// `[formats]` and the `formats` of each notifier, as they are written.
+    pub fn formats_tables(&self) -> Vec<(&'static str, &Formats)> {
+        let overrides = [
+            ("twitter.formats", self.twitter.as_ref().map(|c| &c.formats)),
+            ("ifttt.formats", self.ifttt.as_ref().map(|c| &c.formats)),
+            ("command.formats", self.command.as_ref().map(|c| &c.formats)),
+            ("stdout.formats", self.stdout.as_ref().map(|c| &c.formats)),
+        ];
+
+        std::iter::once(("formats", &self.formats))
+            .chain(
+                overrides
+                    .into_iter()
+                    .filter_map(|(table, formats)| formats.map(|formats| (table, formats))),
+            )
+            .collect()
+    }
+
+    /// the problems in the values which are well-typed but can't work.
+    fn problems(&self, locator: &Locator) -> Vec<Problem> {
+        let mut problems = Vec::new();
+        let mut problem = |table: &str, key: &str, message: String| {
+            problems.push(Problem::new(
+                locator.key(table, key),
+                format!("[{}] {}", table, message),
+            ));
+        };
+
+        if self.mcnotify.check_interval == 0 {
+            problem(
+                "mcnotify",
+                "check_interval",
+                "check_interval must be greater than 0".to_owned(),
+            );
+        }
+        if self.mcnotify.min_check_interval == Some(0) {
+            problem(
+                "mcnotify",
+                "min_check_interval",
+                "min_check_interval must be greater than 0".to_owned(),
+            );
+        }
+        if let Some(Err(e)) = self
+            .mcnotify
+            .timezone
+            .as_ref()
+            .map(|s| s.parse::<Timezone>())
+        {
+            problem("mcnotify", "timezone", e);
+        }
+
+        for (table, formats) in self.formats_tables() {
+            if let Some(Err(e)) = formats.locale.as_ref().map(|s| s.parse::<Locale>()) {
+                problem(table, "locale", e);
+            }
+            if let Some(Err(e)) = formats.timezone.as_ref().map(|s| s.parse::<Timezone>()) {
+                problem(table, "timezone", e);
+            }
+            if let Some(Err(e)) = formats
+                .duration_style
+                .as_ref()
+                .map(|s| s.parse::<DurationStyle>())
+            {
+                problem(table, "duration_style", e);
+            }
+        }
+
+        if let Some(ref twitter) = self.twitter {
+            let credentials = [
+                ("consumer_key", &twitter.consumer_key),
+                ("consumer_secret", &twitter.consumer_secret),
+                ("access_key", &twitter.access_key),
+                ("access_secret", &twitter.access_secret),
+            ];
+            for (key, value) in credentials {
+                if value.trim().is_empty() {
+                    problem("twitter", key, format!("{} is empty", key));
+                }
+            }
+        }
+
+        if let Some(ref ifttt) = self.ifttt {
+            if !is_http_url(&ifttt.endpoint_url) {
+                problem(
+                    "ifttt",
+                    "endpoint_url",
+                    format!("endpoint_url is not an HTTP URL: {}", ifttt.endpoint_url),
+                );
+            }
+        }
+
+        if let Some(ref command) = self.command {
+            if !command_exists(&command.command) {
+                problem(
+                    "command",
+                    "command",
+                    format!("command not found: {}", command.command),
+                );
+            }
+        }
+
+        // `[[quiet_hours]]` and `[[digest]]` are found by their index
+        let mut element_problem = |table: &str, nth: usize, key: &str, message: String| {
+            problems.push(Problem::new(
+                locator.element_key(table, nth, key),
+                format!("[{}] {}", table, message),
+            ));
+        };
+
+        for (nth, conf) in self.quiet_hours.iter().enumerate() {
+            if let Err(e) = TimeWindow::parse_time(&conf.start) {
+                element_problem("quiet_hours", nth, "start", e);
+            }
+            if let Err(e) = TimeWindow::parse_time(&conf.end) {
+                element_problem("quiet_hours", nth, "end", e);
+            }
+            for day in &conf.days {
+                if let Err(e) = TimeWindow::parse_day(day) {
+                    element_problem("quiet_hours", nth, "days", e);
+                }
+            }
+            if let Some(Err(e)) = conf.action.as_ref().map(|s| s.parse::<QuietAction>()) {
+                element_problem("quiet_hours", nth, "action", e);
+            }
+        }
+
+        for (nth, conf) in self.digests.iter().enumerate() {
+            if conf.schedule.parse::<Schedule>().is_err() {
+                element_problem(
+                    "digest",
+                    nth,
+                    "schedule",
+                    format!("invalid schedule: {}", conf.schedule),
+                );
+            }
+        }
+
+        problems
+    }
+}
+
+/// deserializes the source, reporting unknown keys.
+/// serde stops at the first unknown key, so it is removed to find the next one.
+fn parse(source: &str, locator: &Locator) -> (Option<Config>, Vec<Problem>) {
+    let mut lines: Vec<&str> = source.lines().collect();
+    let mut problems = Vec::new();
+
+    loop {
+        let error = match toml::from_str::<Config>(&lines.join("\n")) {
+            Ok(config) => return (Some(config), problems),
+            Err(error) => error,
+        };
+        let message = error.to_string();
+
+        let unknown = match UnknownKey::from_message(&message) {
+            Some(unknown) => unknown,
+            None => {
+                problems.push(Problem::new(
+                    error.line_col().map(|(line, _)| line + 1),
+                    without_position(&message).to_owned(),
+                ));
+                return (None, problems);
+            }
+        };
+
+        // the position of an unknown key is the one of some table, so it is searched by the name
+        match unknown.lines(locator, &lines) {
+            Some((start, end)) => {
+                problems.push(Problem::new(Some(start + 1), unknown.to_string()));
+                for line in &mut lines[start..end] {
+                    *line = "";
+                }
+            }
+            None => {
+                let line = locator.table(unknown.table);
+                problems.push(Problem::new(line, unknown.to_string()));
+                return (None, problems);
+            }
+        }
+    }
+}
+
+/// `unknown field `zz`, expected `y` for key `t` at line 3 column 1`
+struct UnknownKey<'a> {
+    key: &'a str,
+    /// empty at the top level
+    table: &'a str,
+    expected: &'a str,
+}
+
+impl<'a> UnknownKey<'a> {
+    fn from_message(message: &'a str) -> Option<Self> {
+        let rest = message.strip_prefix("unknown field `")?;
+        let (key, rest) = rest.split_once('`')?;
+        let rest = without_position(rest);
+
+        let (expected, table) = match rest.split_once(" for key `") {
+            Some((expected, table)) => (expected, table.trim_end_matches('`')),
+            None => (rest, ""),
+        };
+
+        Some(Self {
+            key,
+            table,
+            expected: expected.trim_start_matches(", "),
+        })
+    }
+
+    /// the range of the lines of the key, or of the table if the key is a table,
+    /// which are not removed from `lines` yet.
+    fn lines(&self, locator: &Locator, lines: &[&str]) -> Option<(usize, usize)> {
+        let key = locator
+            .starts_of(self.table)
+            .into_iter()
+            .filter_map(|start| locator.key_from(start, self.key))
+            .find(|&index| !lines[index].is_empty());
+        if let Some(index) = key {
+            return Some((index, locator.value_end(index)));
+        }
+
+        locator
+            .starts_of(&self.path())
+            .into_iter()
+            .find(|&start| !lines[start - 1].is_empty())
+            .map(|start| (start - 1, locator.end_of(start)))
+    }
+
+    fn path(&self) -> String {
+        if self.table.is_empty() {
+            self.key.to_owned()
+        } else {
+            format!("{}.{}", self.table, self.key)
+        }
+    }
+}
+
+impl<'a> fmt::Display for UnknownKey<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.table.is_empty() {
+            write!(f, "unknown key `{}` ({})", self.key, self.expected)
+        } else {
+            write!(
+                f,
+                "unknown key `{}` in [{}] ({})",
+                self.key, self.table, self.expected
+            )
+        }
+    }
+}
+
+/// removes ` at line 3 column 1` which toml appends.
+fn without_position(message: &str) -> &str {
+    match message.rfind(" at line ") {
+        Some(index) => &message[..index],
+        None => message,
+    }
+}
+
+fn is_http_url(url: &str) -> bool {
+    match reqwest::Url::parse(url) {
+        Ok(url) => (url.scheme() == "http" || url.scheme() == "https") && url.host().is_some(),
+        Err(_) => false,
+    }
+}
+
+/// a path, or a name found in `PATH`.
+fn command_exists(command: &str) -> bool {
+    if command.contains('/') {
+        return Path::new(command).is_file();
+    }
+
+    match env::var_os("PATH") {
+        Some(paths) => env::split_paths(&paths).any(|dir| dir.join(command).is_file()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOURCE: &str = r#"# mcnotify
+[mcnotify]
+check_interval = 0
+chek_interval = 20
+
+[address]
+hostname = "localhost"
+port = 25565
+
+[formats]
+join_fmt = """
+{players} joined.
+"""
+laeve_fmt = """
+{players} left.
+"""
+leave_fmt = "{players} left."
+
+[twitter]
+consumer_key = ""
+consumer_secret = "secret"
+access_key = "key"
+access_secret = "secret"
+token = "x"
+
+[ifttt]
+endpoint_url = "maker.ifttt.com/trigger"
+
+[stdout.format]
+time_fmt = ""
+
+[ifttt.formats]
+locale = "fr"
+
+[[quiet_hours]]
+start = "23:00"
+end = "7am"
+
+[[quiet_hours]]
+start = "22:00"
+end = "06:00"
+action = "hodl"
+
+[[digest]]
+schedule = "0 25 * * *"
+"#;
+
+    #[test]
+    fn locator_finds_keys() {
+        let locator = Locator::new(SOURCE);
+
+        assert_eq!(locator.table("mcnotify"), Some(2));
+        assert_eq!(locator.key("mcnotify", "check_interval"), Some(3));
+        assert_eq!(locator.key("formats", "leave_fmt"), Some(17));
+        assert_eq!(locator.key("formats", "laeve_fmt"), Some(14));
+        assert_eq!(locator.key("twitter", "access_key"), Some(22));
+        assert_eq!(locator.key("twitter", "hostname"), None);
+        assert_eq!(locator.key("", "check_interval"), None);
+        assert_eq!(locator.element_key("quiet_hours", 1, "action"), Some(42));
+        assert_eq!(locator.element_key("quiet_hours", 0, "days"), Some(35));
+        assert_eq!(locator.element_key("quiet_hours", 2, "start"), None);
+    }
+
+    #[test]
+    fn config_check_reports_every_problem() {
+        let (config, problems) = Config::check(SOURCE);
+        assert!(config.is_some());
+
+        let problems: Vec<String> = problems.iter().map(|p| p.to_string()).collect();
+        assert_eq!(problems.len(), 11);
+        assert!(problems[0].starts_with("line 4: unknown key `chek_interval` in [mcnotify]"));
+        assert!(problems[1].starts_with("line 14: unknown key `laeve_fmt` in [formats]"));
+        assert!(problems[2].starts_with("line 24: unknown key `token` in [twitter]"));
+        assert!(problems[3].starts_with("line 29: unknown key `format` in [stdout]"));
+        assert_eq!(
+            problems[4],
+            "line 3: [mcnotify] check_interval must be greater than 0"
+        );
+        assert!(problems[5].starts_with("line 33: [ifttt.formats] "));
+        assert_eq!(problems[6], "line 20: [twitter] consumer_key is empty");
+        assert_eq!(
+            problems[7],
+            "line 27: [ifttt] endpoint_url is not an HTTP URL: maker.ifttt.com/trigger"
+        );
+        assert_eq!(problems[8], "line 37: [quiet_hours] invalid time: 7am");
+        assert_eq!(problems[9], "line 42: [quiet_hours] unknown action: hodl");
+        assert_eq!(
+            problems[10],
+            "line 45: [digest] invalid schedule: 0 25 * * *"
+        );
+    }
+
+    #[test]
+    fn config_check_reports_syntax_errors() {
+        let (config, problems) = Config::check("[mcnotify]\ncheck_interval = \"20\"\n");
+        assert!(config.is_none());
+        assert_eq!(problems.len(), 1);
+        assert_eq!(problems[0].line, Some(2));
+        assert!(problems[0].message.starts_with("invalid type"));
+
+        let (_, problems) = Config::check("[mcnotify\n");
+        assert_eq!(problems[0].line, Some(1));
+    }
+}
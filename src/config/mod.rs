@@ -0,0 +1,490 @@
+#[cfg(unix)]
+extern crate xdg_basedir;
+
+mod check;
+
+pub use self::check::{Locator, Problem};
+
+use std::convert;
+#[cfg(unix)]
+use std::io;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug)]
+pub enum Error {
+    XDGError(xdg_basedir::Error),
+    IoError(io::Error),
+    /// the problems found by `Config::check`
+    InvalidConfig(Vec<Problem>),
+    ConfigNotFound,
+    CondigDirsIsEmpty,
+}
+
+impl_convert_for_error!(xdg_basedir::Error, Error::XDGError);
+impl_convert_for_error!(io::Error, Error::IoError);
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Config {
+    pub mcnotify: McNotify,
+    pub address: Address,
+    #[serde(default)]
+    pub formats: Formats,
+    pub twitter: Option<TwitterConfig>,
+    pub ifttt: Option<IFTTTConfig>,
+    pub command: Option<CommandConfig>,
+    pub stdout: Option<StdoutConfig>,
+    pub player_count: Option<PlayerCountConfig>,
+    pub player_filter: Option<PlayerFilterConfig>,
+    pub history: Option<HistoryConfig>,
+    #[serde(default, rename = "digest")]
+    pub digests: Vec<DigestConfig>,
+    #[serde(default)]
+    pub quiet_hours: Vec<QuietHoursConfig>,
+    pub control: Option<ControlConfig>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct McNotify {
+    pub check_interval: u16,
+
+    /// (sec) The shortest interval, used while players are online or right after a change.
+    /// `check_interval` by default, which disables the adaptive interval.
+    pub min_check_interval: Option<u64>,
+
+    /// (sec) The longest interval, reached while the server is empty or down.
+    /// `check_interval` by default.
+    pub max_check_interval: Option<u64>,
+
+    /// The number of consecutive failed checks required to announce that the server is down.
+    pub down_threshold: Option<u32>,
+
+    /// The number of consecutive successful checks required to announce that the server recovered.
+    pub recover_threshold: Option<u32>,
+
+    /// (sec) How long the server has to be unreachable before it is announced as down.
+    pub down_grace_period: Option<u64>,
+
+    /// (sec) Player changes within this window after the first one are merged into one notification.
+    /// A player who joins and leaves within the window is not announced. Disabled by default.
+    pub coalesce_window: Option<u64>,
+
+    /// (sec) A player who leaves and comes back within this is announced neither to have left
+    /// nor joined, and the session continues. Disabled by default.
+    pub rejoin_grace_period: Option<u64>,
+
+    /// A file to save the last known status into.
+    /// `$XDG_STATE_HOME/mcnotify/<hostname>_<port>.json` is used by default.
+    pub state_file: Option<String>,
+
+    /// (sec) The saved status older than this is ignored on start.
+    pub state_max_age: Option<u64>,
+
+    /// A file to record every player who has ever been seen.
+    /// `$XDG_DATA_HOME/mcnotify/players_<hostname>_<port>.json` is used by default.
+    pub player_registry: Option<String>,
+
+    /// The timezone in which digests and quiet hours are evaluated, e.g. `Asia/Tokyo`,
+    /// and the one of the times in the messages unless `[formats]` sets it.
+    /// The local timezone of the system is used by default.
+    pub timezone: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Address {
+    pub hostname: String,
+    pub port: u16,
+}
+
+/// The templates of the messages. The ones which are not set are taken from the bundle of `locale`.
+/// See README.md for the syntax and the variables.
+#[derive(Deserialize, PartialEq, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Formats {
+    /// The language of the default formats, the plural forms, durations and the names in `time_fmt`,
+    /// e.g. `en` (default) or `ja`.
+    pub locale: Option<String>,
+
+    /// The timezone of `time_fmt`, e.g. `Asia/Tokyo`. `timezone` in `[mcnotify]` by default.
+    pub timezone: Option<String>,
+
+    /// How durations such as session lengths and downtimes are written:
+    /// `short` (default) e.g. "2h13m", or `long` e.g. "2 hours 13 minutes".
+    pub duration_style: Option<String>,
+
+    /// A notification message sent when the server recovered.
+    pub recover_msg: Option<String>,
+
+    /// A notification message sent when the server down.
+    pub down_msg: Option<String>,
+
+    /// A notification message sent when some player joined the server.
+    pub join_fmt: Option<String>,
+
+    /// A notification message sent when some player left the server.
+    pub leave_fmt: Option<String>,
+
+    /// A notification message sent when players joined the server
+    /// but the server doesn't tell who they are.
+    pub join_count_fmt: Option<String>,
+
+    /// A notification message sent when players left the server
+    /// but the server doesn't tell who they are.
+    pub leave_count_fmt: Option<String>,
+
+    /// A notification message sent when the description (MOTD) of the server changed.
+    /// An empty string disables the notification.
+    pub motd_change_fmt: Option<String>,
+
+    /// A notification message sent when the version of the server changed.
+    /// An empty string disables the notification.
+    pub version_change_fmt: Option<String>,
+
+    /// A notification message sent when the maximum number of players changed.
+    /// An empty string disables the notification.
+    pub max_players_change_fmt: Option<String>,
+
+    /// A notification message sent when the number of online players reached a threshold
+    /// in `[player_count]`.
+    pub count_reached_fmt: Option<String>,
+
+    /// A notification message sent when the server became full.
+    pub full_fmt: Option<String>,
+
+    /// A notification message sent when all players left the server.
+    pub empty_msg: Option<String>,
+
+    /// A notification message sent when a player joined the server for the first time.
+    /// An empty string disables the notification.
+    pub new_player_fmt: Option<String>,
+
+    /// A summary message sent on the schedules in `[[digest]]`.
+    pub digest_fmt: Option<String>,
+
+    /// A notification message sent when mcnotify stops on SIGTERM or SIGINT.
+    /// Disabled by default.
+    pub stopped_msg: Option<String>,
+
+    /// The online players, appended to join, leave and recover messages.
+    pub players_fmt: Option<String>,
+
+    /// The time at the top of the messages, in the `strftime` syntax.
+    /// If a format of a message places the time by `{time}`, it is not put at the top of that message.
+    pub time_fmt: Option<String>,
+}
+
+impl Formats {
+    /// Returns the formats replaced with the ones set in `overrides`, e.g. `[twitter.formats]`.
+    /// If `overrides` sets `locale`, the formats it doesn't set are taken from the bundle of
+    /// that locale instead of these, and only `timezone` and `duration_style` are kept.
+    pub fn with_overrides(&self, overrides: &Formats) -> Formats {
+        let settings = Formats {
+            timezone: self.timezone.clone(),
+            duration_style: self.duration_style.clone(),
+            ..Formats::default()
+        };
+        let base = match overrides.locale {
+            Some(_) => &settings,
+            None => self,
+        };
+        let pick = |value: &Option<String>, overridden: &Option<String>| {
+            overridden.clone().or_else(|| value.clone())
+        };
+
+        Formats {
+            locale: pick(&self.locale, &overrides.locale),
+            timezone: pick(&base.timezone, &overrides.timezone),
+            duration_style: pick(&base.duration_style, &overrides.duration_style),
+            recover_msg: pick(&base.recover_msg, &overrides.recover_msg),
+            down_msg: pick(&base.down_msg, &overrides.down_msg),
+            join_fmt: pick(&base.join_fmt, &overrides.join_fmt),
+            leave_fmt: pick(&base.leave_fmt, &overrides.leave_fmt),
+            join_count_fmt: pick(&base.join_count_fmt, &overrides.join_count_fmt),
+            leave_count_fmt: pick(&base.leave_count_fmt, &overrides.leave_count_fmt),
+            motd_change_fmt: pick(&base.motd_change_fmt, &overrides.motd_change_fmt),
+            version_change_fmt: pick(&base.version_change_fmt, &overrides.version_change_fmt),
+            max_players_change_fmt: pick(
+                &base.max_players_change_fmt,
+                &overrides.max_players_change_fmt,
+            ),
+            count_reached_fmt: pick(&base.count_reached_fmt, &overrides.count_reached_fmt),
+            full_fmt: pick(&base.full_fmt, &overrides.full_fmt),
+            empty_msg: pick(&base.empty_msg, &overrides.empty_msg),
+            new_player_fmt: pick(&base.new_player_fmt, &overrides.new_player_fmt),
+            digest_fmt: pick(&base.digest_fmt, &overrides.digest_fmt),
+            stopped_msg: pick(&base.stopped_msg, &overrides.stopped_msg),
+            players_fmt: pick(&base.players_fmt, &overrides.players_fmt),
+            time_fmt: pick(&base.time_fmt, &overrides.time_fmt),
+        }
+    }
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TwitterConfig {
+    pub consumer_key: String,
+    pub consumer_secret: String,
+    pub access_key: String,
+    pub access_secret: String,
+    #[serde(default)]
+    pub formats: Formats,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct IFTTTConfig {
+    pub endpoint_url: String,
+
+    /// Longer messages are split into numbered parts, which are posted in order.
+    /// `truncate` is the previous name.
+    #[serde(alias = "truncate")]
+    pub max_length: Option<usize>,
+
+    #[serde(default)]
+    pub formats: Formats,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct CommandConfig {
+    pub command: String,
+    pub args: Vec<String>,
+    pub pipe: bool,
+
+    /// Writes the message in JSON instead of the text into stdin when `pipe` is true.
+    #[serde(default)]
+    pub json: bool,
+
+    #[serde(default)]
+    pub formats: Formats,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct StdoutConfig {
+    #[serde(default)]
+    pub formats: Formats,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerCountConfig {
+    /// Notify when the number of online players reaches one of them.
+    #[serde(default)]
+    pub thresholds: Vec<u32>,
+
+    /// Notify when the server becomes full.
+    #[serde(default)]
+    pub full: bool,
+
+    /// Notify when all players left the server.
+    #[serde(default)]
+    pub empty: bool,
+
+    /// The number of players by which the count has to move back
+    /// before the same notification is sent again.
+    pub hysteresis: Option<u32>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerFilterConfig {
+    /// Announce only these players (names or UUIDs) if not empty.
+    #[serde(default)]
+    pub only: Vec<String>,
+
+    /// Never announce these players (names or UUIDs).
+    #[serde(default)]
+    pub ignore: Vec<String>,
+
+    #[serde(default)]
+    pub overrides: Vec<PlayerOverrideConfig>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerOverrideConfig {
+    /// The name or the UUID of the player.
+    pub player: String,
+
+    /// Announce (or hide) the player regardless of `only` and `ignore`.
+    pub announce: Option<bool>,
+
+    /// A name shown instead of the player name.
+    pub display_name: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct HistoryConfig {
+    /// A SQLite database to record every status check into.
+    /// `$XDG_DATA_HOME/mcnotify/history.sqlite3` is used by default.
+    pub path: Option<String>,
+
+    /// (day) Records older than this are removed.
+    pub retention_days: Option<u32>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct DigestConfig {
+    /// A cron expression (`minute hour day-of-month month day-of-week`) in `timezone`,
+    /// e.g. `0 23 * * *` for every day at 23:00.
+    pub schedule: String,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct QuietHoursConfig {
+    /// `HH:MM` in `timezone`. The window ends on the next day if `end` is earlier than `start`.
+    pub start: String,
+    pub end: String,
+
+    /// The days on which the window starts, e.g. `["sat", "sun"]`. Every day if empty.
+    #[serde(default)]
+    pub days: Vec<String>,
+
+    /// The kinds of events kept quiet. Every event except `down` if empty.
+    #[serde(default)]
+    pub events: Vec<String>,
+
+    /// The notifiers kept quiet, e.g. `["twitter"]`. Every notifier if empty.
+    #[serde(default)]
+    pub notifiers: Vec<String>,
+
+    /// `hold` (default) sends the messages together when the window ends, and `drop` discards them.
+    pub action: Option<String>,
+}
+
+#[derive(Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ControlConfig {
+    /// A Unix domain socket to accept commands from `mcnotify ctl`.
+    /// `$XDG_RUNTIME_DIR/mcnotify/<hostname>_<port>.sock` is used by default.
+    pub socket: Option<String>,
+}
+
+impl Config {
+    /// Reads the configuration, which fails if `Config::check` finds any problem.
+    pub fn read_path(path: &Path) -> Result<Config, Error> {
+        let source = Self::read_source(path)?;
+
+        match Config::check(&source) {
+            (Some(config), problems) if problems.is_empty() => Ok(config),
+            (_, problems) => Err(Error::InvalidConfig(problems)),
+        }
+    }
+
+    pub fn read_source(path: &Path) -> Result<String, Error> {
+        use std::fs::File;
+        use std::io::Read;
+
+        let mut file = File::open(path)?;
+
+        let mut string = String::with_capacity(256);
+        file.read_to_string(&mut string)?;
+
+        Ok(string)
+    }
+
+    /// Finds `mcnotify/config.toml` in `XDG_CONFIG_HOME` or `XDG_CONFIG_DIRS`.
+    pub fn default_path() -> Result<PathBuf, Error> {
+        // XDG_CONFIG_HOME
+        let mut pathbuf = xdg_basedir::get_config_home()?;
+        if Self::build_config_path(&mut pathbuf).exists() {
+            return Ok(pathbuf);
+        }
+
+        // XDG_CONFIG_DIRS
+        let dirs = xdg_basedir::get_config_dirs();
+
+        if dirs.is_empty() {
+            return Err(Error::CondigDirsIsEmpty);
+        }
+
+        for mut dir in dirs.into_iter() {
+            if Self::build_config_path(&mut dir).exists() {
+                return Ok(dir);
+            }
+        }
+
+        Err(Error::ConfigNotFound)
+    }
+
+    /// Returns the names of the sections which differ from `other`.
+    pub fn changed_sections(&self, other: &Config) -> Vec<&'static str> {
+        let sections = [
+            ("mcnotify", self.mcnotify != other.mcnotify),
+            ("address", self.address != other.address),
+            ("formats", self.formats != other.formats),
+            ("twitter", self.twitter != other.twitter),
+            ("ifttt", self.ifttt != other.ifttt),
+            ("command", self.command != other.command),
+            ("stdout", self.stdout != other.stdout),
+            ("player_count", self.player_count != other.player_count),
+            ("player_filter", self.player_filter != other.player_filter),
+            ("history", self.history != other.history),
+            ("digest", self.digests != other.digests),
+            ("quiet_hours", self.quiet_hours != other.quiet_hours),
+            ("control", self.control != other.control),
+        ];
+
+        sections
+            .iter()
+            .filter(|(_, changed)| *changed)
+            .map(|(name, _)| *name)
+            .collect()
+    }
+
+    fn build_config_path(pathbuf: &mut PathBuf) -> &Path {
+        const CONFIG_DIR: &str = "mcnotify";
+        const CONFIG_PATH: &str = "config.toml";
+
+        pathbuf.push(CONFIG_DIR);
+        pathbuf.push(CONFIG_PATH);
+
+        pathbuf.as_path()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_with_overrides() {
+        let formats = Formats {
+            locale: Some("en".to_owned()),
+            timezone: Some("Asia/Tokyo".to_owned()),
+            join_fmt: Some("{players} joined.".to_owned()),
+            leave_fmt: Some("{players} left.".to_owned()),
+            ..Formats::default()
+        };
+
+        let overrides = Formats {
+            leave_fmt: Some("bye {players}".to_owned()),
+            ..Formats::default()
+        };
+        let merged = formats.with_overrides(&overrides);
+        assert_eq!(merged.locale.as_deref(), Some("en"));
+        assert_eq!(merged.join_fmt.as_deref(), Some("{players} joined."));
+        assert_eq!(merged.leave_fmt.as_deref(), Some("bye {players}"));
+
+        // the formats of another language come from its bundle, not from the English ones
+        let overrides = Formats {
+            locale: Some("ja".to_owned()),
+            leave_fmt: Some("{players} が退出しました。".to_owned()),
+            ..Formats::default()
+        };
+        let merged = formats.with_overrides(&overrides);
+        assert_eq!(merged.locale.as_deref(), Some("ja"));
+        assert_eq!(merged.timezone.as_deref(), Some("Asia/Tokyo"));
+        assert_eq!(merged.join_fmt, None);
+        assert_eq!(
+            merged.leave_fmt.as_deref(),
+            Some("{players} が退出しました。")
+        );
+    }
+}
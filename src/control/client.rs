@@ -0,0 +1,17 @@
+use super::{Command, Error};
+use std::io::{Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+/// sends the command to the mcnotify listening on `path`, and returns the response.
+pub fn send(path: &Path, command: &Command) -> Result<String, Error> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", command)?;
+    stream.shutdown(Shutdown::Write)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+
+    Ok(response.trim_end().to_owned())
+}
@@ -0,0 +1,7 @@
+mod client;
+mod protocol;
+mod server;
+
+pub use self::client::send;
+pub use self::protocol::{Command, StatusResponse};
+pub use self::server::{ControlServer, Error, Request};
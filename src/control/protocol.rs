@@ -0,0 +1,101 @@
+use crate::history::parse_period;
+use crate::status_checker::Status;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt;
+use std::str::FromStr;
+
+/// a request to the running mcnotify, sent as a line of text.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    /// stops sending notifications, for the period if given. the status is still checked.
+    Pause(Option<Duration>),
+    Resume,
+    /// checks the status right now.
+    Check,
+    /// returns the current status as JSON.
+    Status,
+    /// sends the message to every notifier, regardless of the pause and quiet hours.
+    Announce(String),
+}
+
+impl FromStr for Command {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, argument) = match s.split_once(' ') {
+            Some((name, argument)) => (name, argument.trim()),
+            None => (s, ""),
+        };
+
+        match (name, argument) {
+            ("pause", "") => Ok(Command::Pause(None)),
+            ("pause", period) => match parse_period(period) {
+                Some(period) => Ok(Command::Pause(Some(period))),
+                None => Err(format!("invalid period: {}", period)),
+            },
+            ("resume", "") => Ok(Command::Resume),
+            ("check", "") => Ok(Command::Check),
+            ("status", "") => Ok(Command::Status),
+            ("announce", "") => Err("announce needs a message".to_owned()),
+            ("announce", message) => Ok(Command::Announce(message.to_owned())),
+            _ => Err(format!("unknown command: {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Command::Pause(None) => write!(f, "pause"),
+            Command::Pause(Some(period)) => write!(f, "pause {}s", period.num_seconds()),
+            Command::Resume => write!(f, "resume"),
+            Command::Check => write!(f, "check"),
+            Command::Status => write!(f, "status"),
+            // a message is sent as a single line
+            Command::Announce(ref message) => write!(f, "announce {}", message.replace('\n', " ")),
+        }
+    }
+}
+
+/// the response to `status`.
+#[derive(Serialize)]
+pub struct StatusResponse<'a> {
+    pub paused: bool,
+    /// when the notifications are resumed automatically
+    pub resume_at: Option<DateTime<Utc>>,
+    pub status: &'a Status,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_parses_and_formats() {
+        assert_eq!("pause".parse(), Ok(Command::Pause(None)));
+        assert_eq!(
+            "pause 10m".parse(),
+            Ok(Command::Pause(Some(Duration::minutes(10))))
+        );
+        assert_eq!(
+            " announce  Restarting in 5 minutes ".parse(),
+            Ok(Command::Announce("Restarting in 5 minutes".to_owned()))
+        );
+        assert!("pause soon".parse::<Command>().is_err());
+        assert!("pause 9999999999999w".parse::<Command>().is_err());
+        assert!("announce".parse::<Command>().is_err());
+        assert!("status now".parse::<Command>().is_err());
+        assert!("restart".parse::<Command>().is_err());
+
+        for command in [
+            Command::Pause(Some(Duration::minutes(10))),
+            Command::Resume,
+            Command::Check,
+            Command::Status,
+            Command::Announce("hello".to_owned()),
+        ] {
+            assert_eq!(command.to_string().parse(), Ok(command));
+        }
+    }
+}
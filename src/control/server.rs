@@ -0,0 +1,176 @@
+use super::Command;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+use std::time::Duration;
+use std::{convert, env, fs, io, thread};
+
+#[derive(Debug)]
+pub enum Error {
+    IoError(io::Error),
+    RuntimeDirNotFound,
+    /// another mcnotify is listening on the socket.
+    AlreadyInUse(PathBuf),
+}
+
+impl_convert_for_error!(io::Error, Error::IoError);
+
+/// how long a connection waits for the response from the main loop.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// a command received from a client, to be answered by `respond`.
+pub struct Request {
+    pub command: Command,
+    response: Sender<String>,
+}
+
+impl Request {
+    pub fn respond(&self, response: &str) {
+        // the client may have gone already
+        let _ = self.response.send(response.to_owned());
+    }
+}
+
+/// listens on a Unix domain socket in a thread, and passes the commands to the main loop.
+pub struct ControlServer {
+    path: PathBuf,
+    requests: Receiver<Request>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl ControlServer {
+    /// `$XDG_RUNTIME_DIR/mcnotify/<hostname>_<port>.sock`
+    pub fn default_path(hostname: &str, port: u16) -> Result<PathBuf, Error> {
+        const SOCKET_DIR: &str = "mcnotify";
+
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+            .ok_or(Error::RuntimeDirNotFound)?;
+
+        let mut pathbuf = runtime_dir;
+        pathbuf.push(SOCKET_DIR);
+        pathbuf.push(format!("{}_{}.sock", hostname, port));
+
+        Ok(pathbuf)
+    }
+
+    pub fn bind(path: &Path) -> Result<Self, Error> {
+        if path.exists() {
+            // a socket left by a process which didn't stop cleanly
+            if UnixStream::connect(path).is_ok() {
+                return Err(Error::AlreadyInUse(path.to_owned()));
+            }
+            fs::remove_file(path)?;
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let listener = UnixListener::bind(path)?;
+        // only the owner can control mcnotify
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+
+        let (sender, requests) = mpsc::channel();
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let thread_stopped = Arc::clone(&stopped);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_stopped.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                match stream {
+                    Ok(stream) => {
+                        if let Err(e) = Self::handle(stream, &sender) {
+                            warn!("Control connection failed: {:?}", e);
+                        }
+                    }
+                    Err(e) => warn!("Couldn't accept a control connection: {:?}", e),
+                }
+            }
+        });
+
+        Ok(Self {
+            path: path.to_owned(),
+            requests,
+            stopped,
+        })
+    }
+
+    fn handle(stream: UnixStream, sender: &Sender<Request>) -> Result<(), Error> {
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+
+        let response = match line.parse::<Command>() {
+            Ok(command) => {
+                let (response, receiver) = mpsc::channel();
+                sender
+                    .send(Request { command, response })
+                    .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "mcnotify stopped"))?;
+                receiver
+                    .recv_timeout(RESPONSE_TIMEOUT)
+                    .unwrap_or_else(|_| "error: no response".to_owned())
+            }
+            Err(e) => format!("error: {}", e),
+        };
+
+        writeln!(&stream, "{}", response)?;
+        Ok(())
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// waits for a command up to `timeout`.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<Request> {
+        self.requests.recv_timeout(timeout).ok()
+    }
+}
+
+impl Drop for ControlServer {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        // wake the thread up from `accept`
+        let _ = UnixStream::connect(&self.path);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::control::send;
+
+    #[test]
+    fn control_server_answers_requests() {
+        let mut path = env::temp_dir();
+        path.push(format!("mcnotify-control-{}.sock", std::process::id()));
+        let server = ControlServer::bind(&path).unwrap();
+        assert!(matches!(
+            ControlServer::bind(&path),
+            Err(Error::AlreadyInUse(_))
+        ));
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || send(&client_path, &Command::Check).unwrap());
+
+        let request = server.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(request.command, Command::Check);
+        request.respond("ok");
+        assert_eq!(client.join().unwrap(), "ok");
+
+        let response = send(&path, &Command::Announce(String::new())).unwrap();
+        assert!(response.starts_with("error:"));
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
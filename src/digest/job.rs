@@ -0,0 +1,87 @@
+use super::Schedule;
+use crate::timezone::Timezone;
+use chrono::{DateTime, Duration, Utc};
+
+/// keeps track of when a digest should be sent next.
+pub struct DigestJob {
+    schedule: Schedule,
+    timezone: Timezone,
+    next_at: Option<DateTime<Utc>>,
+    last_at: Option<DateTime<Utc>>,
+}
+
+impl DigestJob {
+    pub fn new(schedule: Schedule, timezone: Timezone, now: DateTime<Utc>) -> Self {
+        let next_at = Self::next(&schedule, timezone, now);
+
+        Self {
+            schedule,
+            timezone,
+            next_at,
+            last_at: None,
+        }
+    }
+
+    fn next(
+        schedule: &Schedule,
+        timezone: Timezone,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        let mut after = timezone.to_local(after);
+
+        // skip the times which don't exist because of the daylight saving time
+        for _ in 0..4 {
+            let next = schedule.next_after(after)?;
+            if let Some(next) = timezone.to_utc(&next) {
+                return Some(next);
+            }
+            after = next;
+        }
+
+        None
+    }
+
+    /// returns the period to summarize if the digest is due.
+    /// the first digest covers as long as the interval to the following one.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let next_at = self.next_at?;
+        if now < next_at {
+            return None;
+        }
+
+        let since = match self.last_at {
+            Some(last_at) => last_at,
+            None => {
+                let interval = Self::next(&self.schedule, self.timezone, next_at)
+                    .map_or_else(|| Duration::days(1), |following| following - next_at);
+                next_at - interval
+            }
+        };
+
+        self.last_at = Some(next_at);
+        self.next_at = Self::next(&self.schedule, self.timezone, now);
+
+        Some((since, next_at))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digest_job_polls_on_schedule() {
+        let mut job = DigestJob::new("0 * * * *".parse().unwrap(), Timezone::Local, Utc::now());
+
+        let next_at = job.next_at.unwrap();
+        assert!(job.poll(next_at - Duration::seconds(1)).is_none());
+
+        let (since, until) = job.poll(next_at + Duration::seconds(30)).unwrap();
+        assert_eq!(until, next_at);
+        assert_eq!(until - since, Duration::hours(1));
+        assert!(job.poll(next_at + Duration::seconds(30)).is_none());
+
+        let (since, _) = job.poll(next_at + Duration::hours(1)).unwrap();
+        assert_eq!(since, next_at);
+    }
+}
@@ -0,0 +1,7 @@
+mod job;
+mod schedule;
+mod summary;
+
+pub use self::job::DigestJob;
+pub use self::schedule::{Error, Schedule};
+pub use self::summary::Digest;
@@ -0,0 +1,214 @@
+use chrono::{Datelike, Duration, NaiveDateTime, Timelike};
+use std::str::FromStr;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Error {
+    InvalidSchedule(String),
+}
+
+const WEEKDAYS: &[&str] = &["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+const MONTHS: &[&str] = &[
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+
+/// a set of allowed values of a cron field, as a bit mask.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Field {
+    mask: u64,
+    /// `*` in the day-of-month or the day-of-week field
+    any: bool,
+}
+
+impl Field {
+    fn contains(&self, value: u32) -> bool {
+        self.mask & (1 << value) != 0
+    }
+
+    /// parses a field such as `*`, `*/15`, `1-5`, `mon,wed,fri` or `0-30/10`.
+    fn parse(field: &str, min: u32, max: u32, names: &[&str], offset: u32) -> Option<Self> {
+        let parse_value = |value: &str| -> Option<u32> {
+            if let Some(i) = names
+                .iter()
+                .position(|name| value.eq_ignore_ascii_case(name))
+            {
+                return Some(i as u32 + offset);
+            }
+            value.parse().ok()
+        };
+
+        let mut mask = 0;
+        for part in field.split(',') {
+            let (range, step) = match part.split_once('/') {
+                Some((range, step)) => (range, step.parse().ok().filter(|step| *step > 0)?),
+                None => (part, 1),
+            };
+
+            let (start, end) = if range == "*" {
+                (min, max)
+            } else if let Some((start, end)) = range.split_once('-') {
+                (parse_value(start)?, parse_value(end)?)
+            } else {
+                let value = parse_value(range)?;
+                // `5/15` means from 5 to the end every 15
+                (value, if part.contains('/') { max } else { value })
+            };
+
+            if start < min || end > max || start > end {
+                return None;
+            }
+
+            for value in (start..=end).step_by(step) {
+                mask |= 1 << value;
+            }
+        }
+
+        Some(Self {
+            mask,
+            any: field == "*",
+        })
+    }
+}
+
+/// a cron expression: `minute hour day-of-month month day-of-week`.
+/// `@hourly`, `@daily`, `@weekly` and `@monthly` are also accepted.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schedule {
+    minute: Field,
+    hour: Field,
+    day: Field,
+    month: Field,
+    weekday: Field,
+}
+
+impl FromStr for Schedule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expression = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            expression => expression,
+        };
+
+        let invalid = || Error::InvalidSchedule(s.to_owned());
+
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(invalid());
+        }
+
+        let mut weekday = Field::parse(fields[4], 0, 7, WEEKDAYS, 0).ok_or_else(invalid)?;
+        // both 0 and 7 are Sunday
+        if weekday.contains(7) {
+            weekday.mask |= 1;
+        }
+
+        Ok(Self {
+            minute: Field::parse(fields[0], 0, 59, &[], 0).ok_or_else(invalid)?,
+            hour: Field::parse(fields[1], 0, 23, &[], 0).ok_or_else(invalid)?,
+            day: Field::parse(fields[2], 1, 31, &[], 0).ok_or_else(invalid)?,
+            month: Field::parse(fields[3], 1, 12, MONTHS, 1).ok_or_else(invalid)?,
+            weekday,
+        })
+    }
+}
+
+impl Schedule {
+    fn matches_date(&self, datetime: &NaiveDateTime) -> bool {
+        if !self.month.contains(datetime.month()) {
+            return false;
+        }
+
+        let day = self.day.contains(datetime.day());
+        let weekday = self
+            .weekday
+            .contains(datetime.weekday().num_days_from_sunday());
+
+        // the same as cron, either of them is enough if both are restricted
+        match (self.day.any, self.weekday.any) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// returns the first time after `after` which matches the schedule.
+    pub fn next_after(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after.date().and_hms(after.hour(), after.minute(), 0) + Duration::minutes(1);
+
+        // every combination of a day and a month appears within 4 years, including Feb 29
+        for days in 0..(366 * 4 + 1) {
+            let date = start.date() + Duration::days(days);
+            if !self.matches_date(&date.and_hms(0, 0, 0)) {
+                continue;
+            }
+
+            for hour in (0..24).filter(|hour| self.hour.contains(*hour)) {
+                for minute in (0..60).filter(|minute| self.minute.contains(*minute)) {
+                    let datetime = date.and_hms(hour, minute, 0);
+                    if datetime >= start {
+                        return Some(datetime);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn datetime(y: i32, m: u32, d: u32, h: u32, min: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd(y, m, d).and_hms(h, min, 0)
+    }
+
+    #[test]
+    fn schedule_every_day() {
+        let schedule: Schedule = "0 23 * * *".parse().unwrap();
+
+        assert_eq!(
+            schedule.next_after(datetime(2022, 7, 1, 12, 0)),
+            Some(datetime(2022, 7, 1, 23, 0))
+        );
+        assert_eq!(
+            schedule.next_after(datetime(2022, 7, 1, 23, 0)),
+            Some(datetime(2022, 7, 2, 23, 0))
+        );
+    }
+
+    #[test]
+    fn schedule_weekdays_and_steps() {
+        // 2022-07-04 is Monday
+        let schedule: Schedule = "0 9 * * mon".parse().unwrap();
+        assert_eq!(
+            schedule.next_after(datetime(2022, 7, 1, 12, 0)),
+            Some(datetime(2022, 7, 4, 9, 0))
+        );
+
+        let schedule: Schedule = "*/20 8-9 * * 1-5".parse().unwrap();
+        assert_eq!(
+            schedule.next_after(datetime(2022, 7, 1, 9, 40)),
+            Some(datetime(2022, 7, 4, 8, 0))
+        );
+
+        let schedule: Schedule = "0 0 29 feb *".parse().unwrap();
+        assert_eq!(
+            schedule.next_after(datetime(2022, 7, 1, 0, 0)),
+            Some(datetime(2024, 2, 29, 0, 0))
+        );
+    }
+
+    #[test]
+    fn schedule_rejects_invalid_expressions() {
+        assert!("0 24 * * *".parse::<Schedule>().is_err());
+        assert!("0 9 * *".parse::<Schedule>().is_err());
+        assert!("*/0 * * * *".parse::<Schedule>().is_err());
+        assert!("0 9 * * someday".parse::<Schedule>().is_err());
+        assert!("@weekly".parse::<Schedule>().is_ok());
+    }
+}
@@ -0,0 +1,106 @@
+use crate::history::Probe;
+use crate::models::{Player, Players};
+use chrono::{DateTime, Duration, Utc};
+
+/// a summary of the status history in a period, sent on schedule.
+#[derive(Clone, Debug)]
+pub struct Digest {
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    /// everyone who was online at least once
+    pub players: Players,
+    /// the largest number of online players, and when it was first seen
+    pub peak: Option<(u32, DateTime<Utc>)>,
+    /// the sum of the time every player spent on the server
+    pub playtime: Duration,
+    pub outages: u32,
+}
+
+impl Digest {
+    /// each probe is regarded as lasting until the next one, as well as in the reports.
+    pub fn new(since: DateTime<Utc>, until: DateTime<Utc>, probes: &[Probe]) -> Self {
+        let probes: Vec<&Probe> = probes
+            .iter()
+            .filter(|probe| since <= probe.checked_at && probe.checked_at < until)
+            .collect();
+
+        let mut players: Vec<Player> = Vec::new();
+        let mut peak: Option<(u32, DateTime<Utc>)> = None;
+        let mut playtime = Duration::zero();
+        let mut outages = 0;
+        let mut was_available = true;
+
+        for (i, probe) in probes.iter().enumerate() {
+            if !probe.available && was_available {
+                outages += 1;
+            }
+            was_available = probe.available;
+
+            let online_count = match probe.online_count {
+                Some(online_count) => online_count,
+                None => continue,
+            };
+
+            players.extend(probe.players.iter().cloned());
+
+            if peak.map_or(true, |(count, _)| online_count > count) {
+                peak = Some((online_count, probe.checked_at));
+            }
+
+            let next_checked_at = probes.get(i + 1).map_or(until, |next| next.checked_at);
+            playtime = playtime + (next_checked_at - probe.checked_at) * online_count as i32;
+        }
+
+        Self {
+            since,
+            until,
+            players: Players::from(players),
+            peak,
+            playtime,
+            outages,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(since: DateTime<Utc>, minutes: i64, names: Option<&[&str]>) -> Probe {
+        Probe {
+            checked_at: since + Duration::minutes(minutes),
+            available: names.is_some(),
+            latency: None,
+            online_count: names.map(|names| names.len() as u32),
+            max_players: None,
+            players: names
+                .unwrap_or_default()
+                .iter()
+                .map(|name| Player::new(name, name))
+                .collect(),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn digest_summarizes_probes() {
+        let since = Utc::now();
+        let until = since + Duration::minutes(60);
+        let probes = vec![
+            probe(since, -10, Some(&["Z"])),
+            probe(since, 0, Some(&["A"])),
+            probe(since, 10, Some(&["A", "B"])),
+            probe(since, 20, None),
+            probe(since, 30, Some(&["B"])),
+            probe(since, 40, Some(&["B", "C"])),
+            probe(since, 50, None),
+            probe(since, 60, Some(&["D", "E", "F"])),
+        ];
+
+        let digest = Digest::new(since, until, &probes);
+        assert_eq!(format!("{}", digest.players), "A, B, C");
+        assert_eq!(digest.peak, Some((2, since + Duration::minutes(10))));
+        assert_eq!(digest.playtime, Duration::minutes(60));
+        assert_eq!(digest.outages, 2);
+    }
+}
@@ -0,0 +1,318 @@
+extern crate rusqlite;
+
+use self::rusqlite::{params, Connection};
+use super::migrations::MIGRATIONS;
+use crate::models::{Player, Players};
+use crate::status_checker::Status;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{convert, fs, io, time};
+
+#[derive(Debug)]
+pub enum Error {
+    XDGError(xdg_basedir::Error),
+    IoError(io::Error),
+    SqliteError(rusqlite::Error),
+}
+
+impl_convert_for_error!(xdg_basedir::Error, Error::XDGError);
+impl_convert_for_error!(io::Error, Error::IoError);
+impl_convert_for_error!(rusqlite::Error, Error::SqliteError);
+
+/// a recorded result of a status check.
+#[derive(Clone, Debug)]
+pub struct Probe {
+    pub checked_at: DateTime<Utc>,
+    pub available: bool,
+    pub latency: Option<time::Duration>,
+    pub online_count: Option<u32>,
+    pub max_players: Option<u32>,
+    pub players: Players,
+    /// why the server was unavailable
+    pub reason: Option<String>,
+}
+
+impl Probe {
+    pub fn from_status(status: &Status, checked_at: DateTime<Utc>) -> Self {
+        match *status {
+            Status::Available {
+                online_count,
+                ref current_players,
+                ref server_info,
+                latency,
+                ..
+            } => Self {
+                checked_at,
+                available: true,
+                latency: Some(latency),
+                online_count: Some(online_count),
+                max_players: Some(server_info.max_players()),
+                players: current_players.clone(),
+                reason: None,
+            },
+            Status::Unavailable { ref reason } => Self {
+                checked_at,
+                available: false,
+                latency: None,
+                online_count: None,
+                max_players: None,
+                players: Players::default(),
+                reason: Some(reason.clone()),
+            },
+        }
+    }
+}
+
+/// stores every status check of a server.
+pub struct HistoryDatabase {
+    connection: Connection,
+    hostname: String,
+    port: u16,
+    last_pruned_at: Option<DateTime<Utc>>,
+}
+
+impl HistoryDatabase {
+    pub fn open(path: &Path, hostname: &str, port: u16) -> Result<Self, Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        Self::from_connection(Connection::open(path)?, hostname, port)
+    }
+
+    #[cfg(test)]
+    pub fn open_in_memory(hostname: &str, port: u16) -> Result<Self, Error> {
+        Self::from_connection(Connection::open_in_memory()?, hostname, port)
+    }
+
+    fn from_connection(connection: Connection, hostname: &str, port: u16) -> Result<Self, Error> {
+        connection.execute_batch("PRAGMA foreign_keys = ON;")?;
+
+        let mut database = Self {
+            connection,
+            hostname: hostname.to_owned(),
+            port,
+            last_pruned_at: None,
+        };
+        database.migrate()?;
+
+        Ok(database)
+    }
+
+    /// `$XDG_DATA_HOME/mcnotify/history.sqlite3`
+    pub fn default_path() -> Result<PathBuf, Error> {
+        const DATA_DIR: &str = "mcnotify";
+        const DATABASE_PATH: &str = "history.sqlite3";
+
+        let mut pathbuf = xdg_basedir::get_data_home()?;
+        pathbuf.push(DATA_DIR);
+        pathbuf.push(DATABASE_PATH);
+
+        Ok(pathbuf)
+    }
+
+    fn migrate(&mut self) -> Result<(), Error> {
+        let version: i64 = self
+            .connection
+            .pragma_query_value(None, "user_version", |row| row.get(0))?;
+
+        for (i, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+            let transaction = self.connection.transaction()?;
+            transaction.execute_batch(migration)?;
+            transaction.pragma_update(None, "user_version", (i + 1) as i64)?;
+            transaction.commit()?;
+        }
+
+        Ok(())
+    }
+
+    pub fn record(&mut self, probe: &Probe) -> Result<(), Error> {
+        let transaction = self.connection.transaction()?;
+
+        transaction.execute(
+            "INSERT INTO probes
+                (hostname, port, checked_at, available, latency_ms, online_count, max_players, reason)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+            params![
+                self.hostname,
+                self.port,
+                probe.checked_at.timestamp(),
+                probe.available,
+                probe.latency.map(|latency| latency.as_millis() as i64),
+                probe.online_count,
+                probe.max_players,
+                probe.reason,
+            ],
+        )?;
+        let probe_id = transaction.last_insert_rowid();
+
+        {
+            let mut statement = transaction.prepare(
+                "INSERT INTO probe_players (probe_id, player_id, player_name) VALUES (?1, ?2, ?3)",
+            )?;
+            for player in probe.players.iter() {
+                statement.execute(params![probe_id, player.id(), player.name()])?;
+            }
+        }
+
+        transaction.commit()?;
+
+        Ok(())
+    }
+
+    /// returns every server which has been recorded.
+    pub fn servers(&self) -> Result<Vec<(String, u16)>, Error> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT DISTINCT hostname, port FROM probes ORDER BY hostname, port")?;
+        let mut rows = statement.query([])?;
+
+        let mut servers = Vec::new();
+        while let Some(row) = rows.next()? {
+            servers.push((row.get(0)?, row.get(1)?));
+        }
+
+        Ok(servers)
+    }
+
+    /// returns probes checked at `since` or later, in chronological order.
+    pub fn probes(&self, since: DateTime<Utc>) -> Result<Vec<Probe>, Error> {
+        self.probes_of(&self.hostname, self.port, since)
+    }
+
+    /// the same as `probes`, but of another server recorded in the same database.
+    pub fn probes_of(
+        &self,
+        hostname: &str,
+        port: u16,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<Probe>, Error> {
+        let mut players: HashMap<i64, Vec<Player>> = HashMap::new();
+        {
+            let mut statement = self.connection.prepare(
+                "SELECT pp.probe_id, pp.player_id, pp.player_name
+                    FROM probe_players pp JOIN probes p ON p.id = pp.probe_id
+                    WHERE p.hostname = ?1 AND p.port = ?2 AND p.checked_at >= ?3",
+            )?;
+            let mut rows = statement.query(params![hostname, port, since.timestamp()])?;
+            while let Some(row) = rows.next()? {
+                let id: String = row.get(1)?;
+                let name: String = row.get(2)?;
+                players
+                    .entry(row.get(0)?)
+                    .or_insert_with(Vec::new)
+                    .push(Player::new(&id, &name));
+            }
+        }
+
+        let mut statement = self.connection.prepare(
+            "SELECT id, checked_at, available, latency_ms, online_count, max_players, reason
+                FROM probes
+                WHERE hostname = ?1 AND port = ?2 AND checked_at >= ?3
+                ORDER BY checked_at",
+        )?;
+        let mut rows = statement.query(params![hostname, port, since.timestamp()])?;
+
+        let mut probes = Vec::new();
+        while let Some(row) = rows.next()? {
+            let probe_id: i64 = row.get(0)?;
+            let latency_ms: Option<i64> = row.get(3)?;
+
+            probes.push(Probe {
+                checked_at: Utc.timestamp(row.get(1)?, 0),
+                available: row.get(2)?,
+                latency: latency_ms.map(|ms| time::Duration::from_millis(ms as u64)),
+                online_count: row.get(4)?,
+                max_players: row.get(5)?,
+                players: Players::from(players.remove(&probe_id).unwrap_or_default()),
+                reason: row.get(6)?,
+            });
+        }
+
+        Ok(probes)
+    }
+
+    /// removes probes older than `retention`. this runs at most once an hour.
+    pub fn apply_retention(
+        &mut self,
+        retention: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<usize, Error> {
+        if let Some(last_pruned_at) = self.last_pruned_at {
+            if now - last_pruned_at < Duration::hours(1) {
+                return Ok(0);
+            }
+        }
+
+        let count = self.connection.execute(
+            "DELETE FROM probes WHERE checked_at < ?1",
+            params![(now - retention).timestamp()],
+        )?;
+        self.last_pruned_at = Some(now);
+
+        Ok(count)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn probe(checked_at: DateTime<Utc>, names: &[&str]) -> Probe {
+        let players: Vec<Player> = names.iter().map(|name| Player::new(name, name)).collect();
+        Probe {
+            checked_at,
+            available: true,
+            latency: Some(time::Duration::from_millis(12)),
+            online_count: Some(players.len() as u32),
+            max_players: Some(20),
+            players: Players::from(players),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn history_database_records_probes() {
+        let mut database = HistoryDatabase::open_in_memory("localhost", 25565).unwrap();
+        let now = Utc.timestamp(Utc::now().timestamp(), 0);
+
+        database.record(&probe(now, &["A", "B"])).unwrap();
+        database
+            .record(&Probe::from_status(
+                &Status::Unavailable {
+                    reason: "timeout".to_owned(),
+                },
+                now + Duration::minutes(1),
+            ))
+            .unwrap();
+
+        let probes = database.probes(now).unwrap();
+        assert_eq!(probes.len(), 2);
+        assert_eq!(probes[0].checked_at, now);
+        assert_eq!(format!("{}", probes[0].players), "A, B");
+        assert_eq!(probes[0].latency, Some(time::Duration::from_millis(12)));
+        assert!(!probes[1].available);
+        assert_eq!(probes[1].reason.as_deref(), Some("timeout"));
+    }
+
+    #[test]
+    fn history_database_applies_retention() {
+        let mut database = HistoryDatabase::open_in_memory("localhost", 25565).unwrap();
+        let now = Utc::now();
+
+        database
+            .record(&probe(now - Duration::days(100), &["A"]))
+            .unwrap();
+        database.record(&probe(now, &["B"])).unwrap();
+
+        assert_eq!(
+            database.apply_retention(Duration::days(90), now).unwrap(),
+            1
+        );
+
+        let probes = database.probes(now - Duration::days(365)).unwrap();
+        assert_eq!(probes.len(), 1);
+        assert_eq!(format!("{}", probes[0].players), "B");
+    }
+}
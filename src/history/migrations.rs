@@ -0,0 +1,23 @@
+/// schema changes applied in order. `PRAGMA user_version` holds how many of them are applied.
+/// never modify an existing migration; append a new one instead.
+pub const MIGRATIONS: &[&str] = &[
+    // 1: probes and online players
+    "CREATE TABLE probes (
+        id           INTEGER PRIMARY KEY,
+        hostname     TEXT    NOT NULL,
+        port         INTEGER NOT NULL,
+        checked_at   INTEGER NOT NULL, -- unix time in seconds
+        available    INTEGER NOT NULL, -- 0 or 1
+        latency_ms   INTEGER,
+        online_count INTEGER,
+        max_players  INTEGER,
+        reason       TEXT              -- why the server was unavailable
+    );
+    CREATE INDEX probes_server_checked_at ON probes (hostname, port, checked_at);
+    CREATE TABLE probe_players (
+        probe_id    INTEGER NOT NULL REFERENCES probes (id) ON DELETE CASCADE,
+        player_id   TEXT    NOT NULL,
+        player_name TEXT    NOT NULL
+    );
+    CREATE INDEX probe_players_probe_id ON probe_players (probe_id);",
+];
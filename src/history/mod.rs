@@ -0,0 +1,6 @@
+mod database;
+mod migrations;
+mod report;
+
+pub use self::database::{Error, HistoryDatabase, Probe};
+pub use self::report::{parse_period, render, Report, ReportFormat};
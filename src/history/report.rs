@@ -0,0 +1,290 @@
+extern crate serde_json;
+
+use super::Probe;
+use crate::models::format_duration;
+use chrono::{DateTime, Duration, Utc};
+use std::fmt::Write;
+use std::str::FromStr;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ReportFormat {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for ReportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(ReportFormat::Text),
+            "json" => Ok(ReportFormat::Json),
+            "csv" => Ok(ReportFormat::Csv),
+            _ => Err(format!("unknown report format: {}", s)),
+        }
+    }
+}
+
+/// parses a period such as `30m`, `12h`, `7d` or `2w`.
+/// `None` if it is too long for `Duration`.
+pub fn parse_period(s: &str) -> Option<Duration> {
+    let unit_at = s.find(|c: char| !c.is_ascii_digit())?;
+    let (value, unit) = s.split_at(unit_at);
+    let value: i64 = value.parse().ok()?;
+
+    let unit = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => return None,
+    };
+    let seconds = value.checked_mul(unit)?;
+
+    // `Duration` holds milliseconds
+    if seconds > i64::MAX / 1000 {
+        return None;
+    }
+    Some(Duration::seconds(seconds))
+}
+
+/// availability statistics of a server in a period.
+/// each probe is regarded as lasting until the next one, and the period before the first probe is not counted.
+#[derive(Serialize, Debug)]
+pub struct Report {
+    pub hostname: String,
+    pub port: u16,
+    pub since: DateTime<Utc>,
+    pub until: DateTime<Utc>,
+    pub probes: usize,
+    /// (%) `None` if nothing has been recorded
+    pub availability: Option<f64>,
+    pub outages: u32,
+    /// (second)
+    pub longest_outage: Option<i64>,
+    /// (second) the mean duration of the outages which have been recovered
+    pub mean_time_to_recovery: Option<i64>,
+}
+
+impl Report {
+    pub fn new(
+        hostname: &str,
+        port: u16,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+        probes: &[Probe],
+    ) -> Self {
+        let mut available_time = Duration::zero();
+        let mut total_time = Duration::zero();
+        let mut outage_started_at: Option<DateTime<Utc>> = None;
+        let mut outages = 0;
+        let mut longest_outage: Option<Duration> = None;
+        let mut recovered_outages: Vec<Duration> = Vec::new();
+
+        for (i, probe) in probes.iter().enumerate() {
+            let next_checked_at = probes.get(i + 1).map_or(until, |next| next.checked_at);
+            let span = next_checked_at - probe.checked_at;
+
+            total_time = total_time + span;
+            if probe.available {
+                available_time = available_time + span;
+            }
+
+            match (probe.available, outage_started_at) {
+                (false, None) => {
+                    outages += 1;
+                    outage_started_at = Some(probe.checked_at);
+                }
+                (true, Some(started_at)) => {
+                    let outage = probe.checked_at - started_at;
+                    longest_outage = longest_outage.max(Some(outage));
+                    recovered_outages.push(outage);
+                    outage_started_at = None;
+                }
+                _ => {}
+            }
+        }
+
+        // the outage is still going on
+        if let Some(started_at) = outage_started_at {
+            longest_outage = longest_outage.max(Some(until - started_at));
+        }
+
+        let availability = if total_time > Duration::zero() {
+            Some(
+                available_time.num_milliseconds() as f64 * 100.0
+                    / total_time.num_milliseconds() as f64,
+            )
+        } else {
+            None
+        };
+
+        let mean_time_to_recovery = if recovered_outages.is_empty() {
+            None
+        } else {
+            let total: i64 = recovered_outages.iter().map(Duration::num_seconds).sum();
+            Some(total / recovered_outages.len() as i64)
+        };
+
+        Self {
+            hostname: hostname.to_owned(),
+            port,
+            since,
+            until,
+            probes: probes.len(),
+            availability,
+            outages,
+            longest_outage: longest_outage.map(|outage| outage.num_seconds()),
+            mean_time_to_recovery,
+        }
+    }
+}
+
+fn format_seconds(seconds: Option<i64>) -> String {
+    match seconds {
+        Some(seconds) => format_duration(&Duration::seconds(seconds)),
+        None => "-".to_owned(),
+    }
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains(|c| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+fn optional<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+pub fn render(reports: &[Report], format: ReportFormat) -> String {
+    let mut output = String::new();
+
+    match format {
+        ReportFormat::Text => {
+            for report in reports {
+                writeln!(
+                    output,
+                    "{}:{} ({} - {}, {} probes)",
+                    report.hostname,
+                    report.port,
+                    report.since.format("%Y-%m-%d %H:%M"),
+                    report.until.format("%Y-%m-%d %H:%M"),
+                    report.probes
+                )
+                .unwrap();
+                writeln!(
+                    output,
+                    "  availability:          {}",
+                    report
+                        .availability
+                        .map_or_else(|| "-".to_owned(), |a| format!("{:.2}%", a))
+                )
+                .unwrap();
+                writeln!(output, "  outages:               {}", report.outages).unwrap();
+                writeln!(
+                    output,
+                    "  longest outage:        {}",
+                    format_seconds(report.longest_outage)
+                )
+                .unwrap();
+                writeln!(
+                    output,
+                    "  mean time to recovery: {}",
+                    format_seconds(report.mean_time_to_recovery)
+                )
+                .unwrap();
+            }
+        }
+        ReportFormat::Json => {
+            output = serde_json::to_string_pretty(reports).unwrap();
+            output.push('\n');
+        }
+        ReportFormat::Csv => {
+            output.push_str("hostname,port,since,until,probes,availability,outages,longest_outage,mean_time_to_recovery\n");
+            for report in reports {
+                writeln!(
+                    output,
+                    "{},{},{},{},{},{},{},{},{}",
+                    csv_field(&report.hostname),
+                    report.port,
+                    report.since.to_rfc3339(),
+                    report.until.to_rfc3339(),
+                    report.probes,
+                    optional(report.availability.map(|a| format!("{:.4}", a))),
+                    report.outages,
+                    optional(report.longest_outage),
+                    optional(report.mean_time_to_recovery)
+                )
+                .unwrap();
+            }
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Players;
+
+    fn probe(since: DateTime<Utc>, minutes: i64, available: bool) -> Probe {
+        Probe {
+            checked_at: since + Duration::minutes(minutes),
+            available,
+            latency: None,
+            online_count: None,
+            max_players: None,
+            players: Players::default(),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn parse_period_units() {
+        assert_eq!(parse_period("7d"), Some(Duration::days(7)));
+        assert_eq!(parse_period("30m"), Some(Duration::minutes(30)));
+        assert_eq!(parse_period("7"), None);
+        assert_eq!(parse_period("d"), None);
+        assert_eq!(parse_period("7y"), None);
+        assert_eq!(parse_period("999999999999d"), None);
+        assert_eq!(parse_period("9999999999999w"), None);
+    }
+
+    #[test]
+    fn report_counts_outages() {
+        let since = Utc::now();
+        let until = since + Duration::minutes(100);
+        let probes = vec![
+            probe(since, 0, true),
+            probe(since, 10, false),
+            probe(since, 20, false),
+            probe(since, 30, true),
+            probe(since, 60, false),
+            probe(since, 70, true),
+            probe(since, 90, false),
+        ];
+
+        let report = Report::new("localhost", 25565, since, until, &probes);
+        assert_eq!(report.probes, 7);
+        assert_eq!(report.outages, 3);
+        assert_eq!(report.availability, Some(60.0));
+        assert_eq!(report.longest_outage, Some(20 * 60));
+        assert_eq!(report.mean_time_to_recovery, Some(15 * 60));
+    }
+
+    #[test]
+    fn report_without_probes() {
+        let since = Utc::now();
+        let report = Report::new("localhost", 25565, since, since, &[]);
+
+        assert_eq!(report.availability, None);
+        assert_eq!(report.outages, 0);
+        assert!(render(&[report], ReportFormat::Csv).ends_with(",0,,0,,\n"));
+    }
+}
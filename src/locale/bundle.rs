@@ -0,0 +1,108 @@
+use super::Locale;
+
+impl Locale {
+    /// the format used when `[formats]` doesn't set `name`. an empty string disables the message.
+    pub fn default_format(&self, name: &str) -> &'static str {
+        match *self {
+            Locale::English => english(name),
+            Locale::Japanese => japanese(name),
+        }
+    }
+}
+
+fn english(name: &str) -> &'static str {
+    match name {
+        "recover_msg" => "Server is up.",
+        "down_msg" => "Server is down.",
+        "join_fmt" => "{{ players | names }} joined.",
+        "leave_fmt" => "{{ players | names(with_duration=true) }} left.",
+        "join_count_fmt" => {
+            "{{ count }} {{ count | plural(one=\"player\", other=\"players\") }} joined."
+        }
+        "leave_count_fmt" => {
+            "{{ count }} {{ count | plural(one=\"player\", other=\"players\") }} left."
+        }
+        "motd_change_fmt" => "MOTD changed: {{ motd }}",
+        "version_change_fmt" => "Server version changed: {{ old_version }} -> {{ version }}",
+        "max_players_change_fmt" => "Max players changed: {{ old_max }} -> {{ max }}",
+        "count_reached_fmt" => {
+            "{{ count }} {{ count | plural(one=\"player is\", other=\"players are\") }} online now!"
+        }
+        "full_fmt" => "Server is full. ({{ count }}/{{ max }})",
+        "empty_msg" => "Everyone left.",
+        "new_player_fmt" => "Welcome {{ players | names }}, first time here!",
+        "digest_fmt" => {
+            "{{ unique_count }} {{ unique_count | plural(one=\"player\", other=\"players\") }} played: {{ players | names }}. Peak {{ peak_count }} at {{ peak_time }}, total playtime {{ playtime }}, {{ outages }} {{ outages | plural(one=\"outage\", other=\"outages\") }}."
+        }
+        "players_fmt" => {
+            "{{ count }} {{ count | plural(one=\"player\", other=\"players\") }} online: {{ players | names }}"
+        }
+        "time_fmt" => "%a, %b %-d %H:%M",
+        _ => "",
+    }
+}
+
+fn japanese(name: &str) -> &'static str {
+    match name {
+        "recover_msg" => "サーバーが起動しました。",
+        "down_msg" => "サーバーが停止しました。",
+        "join_fmt" => "{{ players | names }} が参加しました。",
+        "leave_fmt" => "{{ players | names(with_duration=true) }} が退出しました。",
+        "join_count_fmt" => "{{ count }}人が参加しました。",
+        "leave_count_fmt" => "{{ count }}人が退出しました。",
+        "motd_change_fmt" => "MOTDが変わりました: {{ motd }}",
+        "version_change_fmt" => {
+            "サーバーのバージョンが変わりました: {{ old_version }} -> {{ version }}"
+        }
+        "max_players_change_fmt" => "最大人数が変わりました: {{ old_max }} -> {{ max }}",
+        "count_reached_fmt" => "{{ count }}人がオンラインです！",
+        "full_fmt" => "サーバーが満員です。({{ count }}/{{ max }})",
+        "empty_msg" => "全員が退出しました。",
+        "new_player_fmt" => "{{ players | names }} さん、はじめまして！",
+        "digest_fmt" => {
+            "{{ unique_count }}人がプレイしました: {{ players | names }}。最大{{ peak_count }}人 ({{ peak_time }})、合計プレイ時間 {{ playtime }}、停止 {{ outages }}回。"
+        }
+        "players_fmt" => "オンライン{{ count }}人: {{ players | names }}",
+        "time_fmt" => "%-m月%-d日(%a) %H:%M",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_locale_has_the_formats() {
+        let names = [
+            "recover_msg",
+            "down_msg",
+            "join_fmt",
+            "leave_fmt",
+            "join_count_fmt",
+            "leave_count_fmt",
+            "motd_change_fmt",
+            "version_change_fmt",
+            "max_players_change_fmt",
+            "count_reached_fmt",
+            "full_fmt",
+            "empty_msg",
+            "new_player_fmt",
+            "digest_fmt",
+            "players_fmt",
+            "time_fmt",
+        ];
+
+        for locale in [Locale::English, Locale::Japanese] {
+            for name in names {
+                assert!(
+                    !locale.default_format(name).is_empty(),
+                    "{:?} {}",
+                    locale,
+                    name
+                );
+            }
+            assert_eq!(locale.default_format("stopped_msg"), "");
+        }
+    }
+}
@@ -0,0 +1,133 @@
+extern crate chrono;
+
+use super::{Locale, Plural};
+use crate::models::format_duration;
+use chrono::Duration;
+use std::str::FromStr;
+
+/// how durations such as session lengths and downtimes are written.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DurationStyle {
+    /// e.g. "2h13m"
+    Short,
+    /// e.g. "2 hours 13 minutes" in the language of the locale
+    Long,
+}
+
+impl Default for DurationStyle {
+    fn default() -> Self {
+        DurationStyle::Short
+    }
+}
+
+impl FromStr for DurationStyle {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "short" => Ok(DurationStyle::Short),
+            "long" => Ok(DurationStyle::Long),
+            _ => Err(format!("unknown duration style: {}", s)),
+        }
+    }
+}
+
+/// the units of the long style, from the largest.
+#[derive(Clone, Copy)]
+enum Unit {
+    Day,
+    Hour,
+    Minute,
+    Second,
+}
+
+impl Locale {
+    pub fn format_duration(&self, duration: &Duration, style: DurationStyle) -> String {
+        match style {
+            DurationStyle::Short => format_duration(duration),
+            DurationStyle::Long => self.humanize_duration(duration),
+        }
+    }
+
+    /// writes the two largest units, e.g. "1 day 3 hours" or "45 seconds".
+    fn humanize_duration(&self, duration: &Duration) -> String {
+        let days = duration.num_days();
+        let hours = duration.num_hours() % 24;
+        let minutes = duration.num_minutes() % 60;
+
+        let units = if days > 0 {
+            vec![(days, Unit::Day), (hours, Unit::Hour)]
+        } else if hours > 0 {
+            vec![(hours, Unit::Hour), (minutes, Unit::Minute)]
+        } else if minutes > 0 {
+            vec![(minutes, Unit::Minute)]
+        } else {
+            vec![(duration.num_seconds().max(0), Unit::Second)]
+        };
+
+        let words: Vec<String> = units
+            .into_iter()
+            .enumerate()
+            // "2 hours" rather than "2 hours 0 minutes"
+            .filter(|&(i, (n, _))| i == 0 || n > 0)
+            .map(|(_, (n, unit))| self.unit(n, unit))
+            .collect();
+
+        match *self {
+            Locale::English => words.join(" "),
+            Locale::Japanese => words.concat(),
+        }
+    }
+
+    fn unit(&self, n: i64, unit: Unit) -> String {
+        let name = match (*self, unit, self.plural(n)) {
+            (Locale::English, Unit::Day, Plural::One) => " day",
+            (Locale::English, Unit::Day, Plural::Other) => " days",
+            (Locale::English, Unit::Hour, Plural::One) => " hour",
+            (Locale::English, Unit::Hour, Plural::Other) => " hours",
+            (Locale::English, Unit::Minute, Plural::One) => " minute",
+            (Locale::English, Unit::Minute, Plural::Other) => " minutes",
+            (Locale::English, Unit::Second, Plural::One) => " second",
+            (Locale::English, Unit::Second, Plural::Other) => " seconds",
+            (Locale::Japanese, Unit::Day, _) => "日",
+            (Locale::Japanese, Unit::Hour, _) => "時間",
+            (Locale::Japanese, Unit::Minute, _) => "分",
+            (Locale::Japanese, Unit::Second, _) => "秒",
+        };
+        format!("{}{}", n, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_humanizes_durations() {
+        let long = |locale: Locale, duration: Duration| {
+            locale.format_duration(&duration, DurationStyle::Long)
+        };
+
+        assert_eq!(
+            Locale::English.format_duration(&Duration::minutes(133), DurationStyle::Short),
+            "2h13m"
+        );
+        assert_eq!(
+            long(Locale::English, Duration::minutes(133)),
+            "2 hours 13 minutes"
+        );
+        assert_eq!(
+            long(Locale::English, Duration::minutes(61)),
+            "1 hour 1 minute"
+        );
+        assert_eq!(long(Locale::English, Duration::hours(2)), "2 hours");
+        assert_eq!(long(Locale::English, Duration::hours(27)), "1 day 3 hours");
+        assert_eq!(long(Locale::English, Duration::seconds(1)), "1 second");
+        assert_eq!(long(Locale::Japanese, Duration::minutes(133)), "2時間13分");
+        assert_eq!(long(Locale::Japanese, Duration::hours(48)), "2日");
+        assert_eq!(long(Locale::Japanese, Duration::seconds(45)), "45秒");
+
+        assert_eq!("long".parse(), Ok(DurationStyle::Long));
+        assert!("longer".parse::<DurationStyle>().is_err());
+    }
+}
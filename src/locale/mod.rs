@@ -0,0 +1,74 @@
+mod bundle;
+mod durations;
+mod time_names;
+
+pub use self::durations::DurationStyle;
+
+use std::str::FromStr;
+
+/// the language of the default formats, plural forms and names in times.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Locale {
+    English,
+    Japanese,
+}
+
+impl Default for Locale {
+    fn default() -> Self {
+        Locale::English
+    }
+}
+
+impl FromStr for Locale {
+    type Err = String;
+
+    /// accepts language tags such as `ja`, `ja-JP` and `ja_JP.UTF-8`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = s
+            .split(|c| c == '-' || c == '_' || c == '.')
+            .next()
+            .unwrap_or(s);
+
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Ok(Locale::English),
+            "ja" => Ok(Locale::Japanese),
+            _ => Err(format!("unknown locale: {}", s)),
+        }
+    }
+}
+
+/// the plural category of a number.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Plural {
+    One,
+    Other,
+}
+
+impl Locale {
+    /// the plural rules of CLDR for integers.
+    pub fn plural(&self, n: i64) -> Plural {
+        match *self {
+            Locale::English if n == 1 => Plural::One,
+            Locale::English => Plural::Other,
+            // Japanese nouns have no plural forms
+            Locale::Japanese => Plural::Other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locale_parses_language_tags() {
+        assert_eq!("en".parse(), Ok(Locale::English));
+        assert_eq!("ja_JP.UTF-8".parse(), Ok(Locale::Japanese));
+        assert_eq!("JA-jp".parse(), Ok(Locale::Japanese));
+        assert!("fr".parse::<Locale>().is_err());
+
+        assert_eq!(Locale::English.plural(1), Plural::One);
+        assert_eq!(Locale::English.plural(0), Plural::Other);
+        assert_eq!(Locale::Japanese.plural(1), Plural::Other);
+    }
+}
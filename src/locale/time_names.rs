@@ -0,0 +1,110 @@
+extern crate chrono;
+
+use super::Locale;
+use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use std::fmt::Display;
+
+const JAPANESE_WEEKDAYS: [&str; 7] = ["月", "火", "水", "木", "金", "土", "日"];
+const JAPANESE_WEEKDAYS_FULL: [&str; 7] = [
+    "月曜日",
+    "火曜日",
+    "水曜日",
+    "木曜日",
+    "金曜日",
+    "土曜日",
+    "日曜日",
+];
+
+impl Locale {
+    /// formats the time with `strftime`-like `fmt`, writing the names of weekdays and months
+    /// (`%a`, `%A`, `%b`, `%B`, `%h`) and `%p` in the language.
+    pub fn format_time<Tz>(&self, datetime: &DateTime<Tz>, fmt: &str) -> String
+    where
+        Tz: TimeZone,
+        Tz::Offset: Display,
+    {
+        if *self == Locale::English {
+            return datetime.format(fmt).to_string();
+        }
+
+        let mut localized = String::with_capacity(fmt.len());
+        let mut chars = fmt.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                localized.push(c);
+                continue;
+            }
+
+            // padding flags such as `%-d` are passed through to chrono
+            let mut spec = String::from('%');
+            while let Some(&flag) = chars.peek() {
+                if !matches!(flag, '-' | '_' | '0' | '^' | '#') {
+                    break;
+                }
+                spec.push(flag);
+                chars.next();
+            }
+
+            match chars.next() {
+                Some(specifier) => match self.name(datetime, specifier) {
+                    Some(name) => localized.push_str(&name),
+                    None => {
+                        spec.push(specifier);
+                        localized.push_str(&spec);
+                    }
+                },
+                None => localized.push_str(&spec),
+            }
+        }
+
+        datetime.format(&localized).to_string()
+    }
+
+    /// the name for the specifier, or `None` if chrono writes it as it is.
+    fn name<Tz: TimeZone>(&self, datetime: &DateTime<Tz>, specifier: char) -> Option<String> {
+        let weekday = datetime.weekday().num_days_from_monday() as usize;
+
+        match (*self, specifier) {
+            (Locale::Japanese, 'a') => Some(JAPANESE_WEEKDAYS[weekday].to_owned()),
+            (Locale::Japanese, 'A') => Some(JAPANESE_WEEKDAYS_FULL[weekday].to_owned()),
+            (Locale::Japanese, 'b') | (Locale::Japanese, 'B') | (Locale::Japanese, 'h') => {
+                Some(format!("{}月", datetime.month()))
+            }
+            (Locale::Japanese, 'p') => Some(
+                if datetime.hour() < 12 {
+                    "午前"
+                } else {
+                    "午後"
+                }
+                .to_owned(),
+            ),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn locale_formats_time_names() {
+        // Saturday
+        let datetime = Utc.ymd(2022, 6, 4).and_hms(15, 4, 0);
+
+        assert_eq!(
+            Locale::English.format_time(&datetime, "%a, %b %-d %H:%M"),
+            "Sat, Jun 4 15:04"
+        );
+        assert_eq!(
+            Locale::Japanese.format_time(&datetime, "%-m月%-d日(%a) %H:%M"),
+            "6月4日(土) 15:04"
+        );
+        assert_eq!(
+            Locale::Japanese.format_time(&datetime, "%B %A %p%I時 100%%"),
+            "6月 土曜日 午後03時 100%"
+        );
+    }
+}
@@ -10,13 +10,24 @@ extern crate tokio;
 pub mod util;
 pub mod application;
 pub mod config;
+pub mod control;
+pub mod digest;
+pub mod history;
+pub mod locale;
 pub mod minecraft;
 pub mod models;
 pub mod notifier;
+pub mod quiet_hours;
+pub mod signals;
 pub mod status_checker;
+pub mod template;
+pub mod timezone;
 
 use crate::application::Application;
-use crate::config::Config;
+use crate::config::{Config, Locator, Problem};
+use crate::control::Command;
+use crate::history::{Report, ReportFormat};
+use crate::status_checker::PlayerRegistry;
 use getopts::Options;
 use std::io::Write;
 use std::path::{Path, PathBuf};
@@ -25,8 +36,27 @@ use std::{env, io, process};
 fn print_usage(program_name: &str, opts: &Options) {
     let pathbuf = PathBuf::from(program_name);
     let filename = pathbuf.file_name().unwrap().to_str().unwrap();
-    let brief = format!("Usage: {} [OPTIONS]\nMinecraft status notifier", filename);
+    let brief = format!(
+        "Usage: {} [OPTIONS] [COMMAND]\nMinecraft status notifier",
+        filename
+    );
     print!("{}", opts.usage(&brief));
+    print!(
+        "
+Commands:
+    config check        report every problem in the configuration with its line
+    players list        list players who have ever been seen
+    players prune DAYS  forget players who have not been seen for DAYS days
+    report              print availability statistics from the status history
+                        (--since PERIOD, e.g. 7d; --format text|json|csv)
+    ctl COMMAND         control the running mcnotify through [control] socket
+        pause [PERIOD]      stop sending notifications (for PERIOD, e.g. 30m)
+        resume              send notifications again
+        check               check the status right now
+        status              print the current status as JSON
+        announce MESSAGE    send MESSAGE to every notifier
+"
+    );
 }
 
 fn print_version(program_name: &str) {
@@ -47,6 +77,18 @@ async fn main() {
         "use specified config file instead of the default",
         "FILE",
     );
+    opts.optopt(
+        "",
+        "since",
+        "report: the period to summarize, e.g. 12h, 7d or 4w (default: 7d)",
+        "PERIOD",
+    );
+    opts.optopt(
+        "",
+        "format",
+        "report: the output format, text, json or csv (default: text)",
+        "FORMAT",
+    );
     opts.optflag("v", "version", "print version");
     opts.optflag("h", "help", "print this message");
 
@@ -68,12 +110,274 @@ async fn main() {
         return;
     }
 
-    let config = match matches.opt_str("config") {
-        Some(custom_conf) => Config::read_path(Path::new(&custom_conf)),
-        None => Config::read_default(),
+    let config_path = match matches.opt_str("config") {
+        Some(custom_conf) => PathBuf::from(custom_conf),
+        None => match Config::default_path() {
+            Ok(path) => path,
+            Err(e) => {
+                writeln!(
+                    &mut io::stderr(),
+                    "Couldn't find the configuration: {:?}",
+                    e
+                )
+                .unwrap();
+                process::exit(1);
+            }
+        },
+    };
+    let (config, problems) = check_config(&config_path);
+
+    if matches.free.first().map(String::as_str) == Some("config") {
+        config_command(&config_path, &problems, &matches.free[1..]);
+        return;
+    }
+
+    let config = match config {
+        Some(config) if problems.is_empty() => config,
+        _ => {
+            print_problems(&config_path, &problems);
+            process::exit(1);
+        }
+    };
+
+    let mut app = Application::new(config, &config_path);
+
+    match matches.free.first().map(String::as_str) {
+        None => app.run(),
+        Some("players") => players_command(&app, &matches.free[1..]),
+        Some("report") => report_command(&app, &matches),
+        Some("ctl") => ctl_command(&app, &matches.free[1..]),
+        Some(command) => {
+            writeln!(&mut io::stderr(), "Unknown command: {}", command).unwrap();
+            process::exit(1);
+        }
+    }
+}
+
+/// reads the configuration and finds every problem in it, including the ones in the formats.
+fn check_config(config_path: &Path) -> (Option<Config>, Vec<Problem>) {
+    let source = match Config::read_source(config_path) {
+        Ok(source) => source,
+        Err(e) => {
+            writeln!(
+                &mut io::stderr(),
+                "Couldn't read {}: {:?}",
+                config_path.display(),
+                e
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
+
+    let (config, mut problems) = Config::check(&source);
+    if let Some(ref config) = config {
+        problems.extend(Application::format_problems(config, &Locator::new(&source)));
+    }
+    problems.sort_by_key(|problem| problem.line);
+
+    (config, problems)
+}
+
+fn print_problems(config_path: &Path, problems: &[Problem]) {
+    for problem in problems {
+        writeln!(&mut io::stderr(), "{}: {}", config_path.display(), problem).unwrap();
+    }
+}
+
+fn config_command(config_path: &Path, problems: &[Problem], args: &[String]) {
+    match args.first().map(String::as_str) {
+        Some("check") => {
+            if problems.is_empty() {
+                println!("{}: OK", config_path.display());
+            } else {
+                print_problems(config_path, problems);
+                process::exit(1);
+            }
+        }
+        _ => {
+            writeln!(&mut io::stderr(), "Usage: config check").unwrap();
+            process::exit(1);
+        }
     }
-    .expect("Couldn't load the configuration...");
+}
+
+fn players_command(app: &Application, args: &[String]) {
+    let path = match app.player_registry_path() {
+        Some(path) => path,
+        None => {
+            writeln!(
+                &mut io::stderr(),
+                "The player registry is not available: $XDG_DATA_HOME or $HOME was not found. \
+                 Please set player_registry in [mcnotify]."
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
+
+    let mut registry = match PlayerRegistry::open(&path) {
+        Ok(registry) => registry,
+        Err(e) => {
+            writeln!(
+                &mut io::stderr(),
+                "Couldn't open the player registry: {:?}",
+                e
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
 
-    let app = Application::new(config);
-    app.run();
+    match args.first().map(String::as_str) {
+        Some("list") => {
+            for (id, entry) in registry.entries() {
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    id,
+                    entry.name,
+                    entry.first_seen.to_rfc3339(),
+                    entry.last_seen.to_rfc3339()
+                );
+            }
+        }
+        Some("prune") => {
+            let before = args
+                .get(1)
+                .and_then(|days| days.parse::<u32>().ok())
+                .and_then(|days| {
+                    chrono::Utc::now().checked_sub_signed(chrono::Duration::days(days.into()))
+                });
+            let before = match before {
+                Some(before) => before,
+                None => {
+                    writeln!(&mut io::stderr(), "Usage: players prune DAYS").unwrap();
+                    process::exit(1);
+                }
+            };
+
+            let count = registry.prune(before);
+
+            if let Err(e) = registry.save() {
+                writeln!(
+                    &mut io::stderr(),
+                    "Couldn't save the player registry: {:?}",
+                    e
+                )
+                .unwrap();
+                process::exit(1);
+            }
+
+            println!("{} players were removed.", count);
+        }
+        _ => {
+            writeln!(&mut io::stderr(), "Usage: players (list | prune DAYS)").unwrap();
+            process::exit(1);
+        }
+    }
+}
+
+fn report_command(app: &Application, matches: &getopts::Matches) {
+    let since = matches.opt_str("since").unwrap_or_else(|| "7d".to_owned());
+    let period = match history::parse_period(&since) {
+        Some(period) => period,
+        None => {
+            writeln!(&mut io::stderr(), "Invalid period: {}", since).unwrap();
+            process::exit(1);
+        }
+    };
+
+    let format: ReportFormat = match matches.opt_str("format") {
+        Some(format) => match format.parse() {
+            Ok(format) => format,
+            Err(e) => {
+                writeln!(&mut io::stderr(), "{}", e).unwrap();
+                process::exit(1);
+            }
+        },
+        None => ReportFormat::Text,
+    };
+
+    let history = match app.open_history() {
+        Some(history) => history,
+        None => {
+            writeln!(
+                &mut io::stderr(),
+                "The status history is not available. Please add [history] to the configuration."
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
+
+    let until = chrono::Utc::now();
+    let since = match until.checked_sub_signed(period) {
+        Some(since) => since,
+        None => {
+            writeln!(&mut io::stderr(), "Invalid period: {}", since).unwrap();
+            process::exit(1);
+        }
+    };
+
+    let reports: Result<Vec<Report>, history::Error> = history.servers().and_then(|servers| {
+        servers
+            .iter()
+            .map(|(hostname, port)| {
+                let probes = history.probes_of(hostname, *port, since)?;
+                Ok(Report::new(hostname, *port, since, until, &probes))
+            })
+            .collect()
+    });
+
+    match reports {
+        Ok(reports) => print!("{}", history::render(&reports, format)),
+        Err(e) => {
+            writeln!(
+                &mut io::stderr(),
+                "Couldn't read the status history: {:?}",
+                e
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    }
+}
+
+fn ctl_command(app: &Application, args: &[String]) {
+    let command: Command = match args.join(" ").parse() {
+        Ok(command) => command,
+        Err(e) => {
+            writeln!(&mut io::stderr(), "{}", e).unwrap();
+            writeln!(
+                &mut io::stderr(),
+                "Usage: ctl (pause [PERIOD] | resume | check | status | announce MESSAGE)"
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
+
+    let path = match app.control_socket_path() {
+        Some(path) => path,
+        None => {
+            writeln!(
+                &mut io::stderr(),
+                "The control socket is not available. Please add [control] to the configuration."
+            )
+            .unwrap();
+            process::exit(1);
+        }
+    };
+
+    match control::send(&path, &command) {
+        Ok(response) if response.starts_with("error:") => {
+            writeln!(&mut io::stderr(), "{}", response).unwrap();
+            process::exit(1);
+        }
+        Ok(response) => println!("{}", response),
+        Err(e) => {
+            writeln!(&mut io::stderr(), "Couldn't connect to mcnotify: {:?}", e).unwrap();
+            process::exit(1);
+        }
+    }
 }
@@ -47,7 +47,19 @@ pub mod chat {
 
     #[derive(Serialize, Deserialize)]
     pub struct Chat {
+        #[serde(default)]
         pub text: String,
+
+        #[serde(default)]
+        pub extra: Vec<Component>,
+    }
+
+    /// an element of `extra`, which is either a plain string or a nested chat object.
+    #[derive(Serialize, Deserialize)]
+    #[serde(untagged)]
+    pub enum Component {
+        Text(String),
+        Chat(Chat),
     }
 
     impl FromStr for Chat {
@@ -56,9 +68,40 @@ pub mod chat {
         fn from_str(text: &str) -> Result<Self, ()> {
             Ok(Chat {
                 text: text.to_owned(),
+                extra: Vec::new(),
             })
         }
     }
+
+    impl Chat {
+        /// concatenates the text of all components, removing formatting codes like "§a".
+        pub fn to_plain_text(&self) -> String {
+            let mut buffer = String::new();
+            self.push_text(&mut buffer);
+
+            let mut plain = String::with_capacity(buffer.len());
+            let mut chars = buffer.chars();
+            while let Some(c) = chars.next() {
+                if c == '§' {
+                    chars.next();
+                } else {
+                    plain.push(c);
+                }
+            }
+
+            plain
+        }
+
+        fn push_text(&self, buffer: &mut String) {
+            buffer.push_str(&self.text);
+            for component in self.extra.iter() {
+                match component {
+                    Component::Text(text) => buffer.push_str(text),
+                    Component::Chat(chat) => chat.push_text(buffer),
+                }
+            }
+        }
+    }
 }
 
 pub mod status {
@@ -94,3 +137,20 @@ pub mod status {
         pub sample: Option<Vec<Player>>,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::status::Status;
+
+    #[test]
+    fn chat_to_plain_text() {
+        let json = r#"{
+            "description": {"text": "§aHello", "extra": [", ", {"text": "§lWorld", "extra": ["!"]}]},
+            "players": {"max": 20, "online": 0},
+            "version": {"name": "1.19", "protocol": 759}
+        }"#;
+        let status: Status = serde_json::from_str(json).unwrap();
+
+        assert_eq!(status.description.to_plain_text(), "Hello, World!");
+    }
+}
@@ -1,9 +1,10 @@
 use crate::minecraft::json_data::status::Player as RawPlayer;
+use chrono::Duration;
 use std::collections::HashSet;
 use std::iter::FromIterator;
 use std::{convert, fmt, ops};
 
-#[derive(Hash, PartialEq, Eq, Clone, Debug)]
+#[derive(Hash, PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
 pub struct Player {
     id: String,
     name: String,
@@ -35,7 +36,7 @@ impl Player {
     }
 }
 
-#[derive(PartialEq, Eq, Clone)]
+#[derive(PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
 pub struct Players {
     players: HashSet<Player>,
 }
@@ -75,6 +76,15 @@ impl<'a, 'b> ops::Sub<&'b Players> for &'a Players {
     }
 }
 
+impl<'a, 'b> ops::BitOr<&'b Players> for &'a Players {
+    type Output = Players;
+
+    fn bitor(self, rhs: &'b Players) -> Players {
+        let union = &self.players | &rhs.players;
+        Players { players: union }
+    }
+}
+
 impl fmt::Display for Players {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let mut players: Vec<String> = self
@@ -99,6 +109,125 @@ impl Players {
     pub fn is_empty(&self) -> bool {
         self.players.is_empty()
     }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Player> {
+        self.players.iter()
+    }
+}
+
+/// properties of the server which are not related to players.
+#[derive(PartialEq, Eq, Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ServerInfo {
+    motd: String,
+    version: String,
+    protocol: u32,
+    max_players: u32,
+}
+
+impl ServerInfo {
+    pub fn new(motd: &str, version: &str, protocol: u32, max_players: u32) -> Self {
+        Self {
+            motd: motd.to_owned(),
+            version: version.to_owned(),
+            protocol,
+            max_players,
+        }
+    }
+
+    pub fn motd(&self) -> &String {
+        &self.motd
+    }
+
+    pub fn version(&self) -> &String {
+        &self.version
+    }
+
+    pub fn protocol(&self) -> u32 {
+        self.protocol
+    }
+
+    pub fn max_players(&self) -> u32 {
+        self.max_players
+    }
+}
+
+/// how long a player stayed on the server.
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Session {
+    player: Player,
+    duration: Duration,
+}
+
+impl Session {
+    pub fn new(player: Player, duration: Duration) -> Self {
+        Self { player, duration }
+    }
+
+    pub fn player(&self) -> &Player {
+        &self.player
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.duration
+    }
+}
+
+impl fmt::Display for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.player.name(),
+            format_duration(&self.duration)
+        )
+    }
+}
+
+/// formats a duration briefly, e.g. "2h13m", "5m" or "45s".
+pub fn format_duration(duration: &Duration) -> String {
+    let hours = duration.num_hours();
+    let minutes = duration.num_minutes() % 60;
+
+    if hours > 0 {
+        format!("{}h{}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", duration.num_seconds())
+    }
+}
+
+/// sessions which have finished at the same time.
+#[derive(PartialEq, Eq, Clone, Debug, Default)]
+pub struct Sessions {
+    sessions: Vec<Session>,
+}
+
+impl convert::From<Vec<Session>> for Sessions {
+    fn from(sessions: Vec<Session>) -> Self {
+        Self { sessions }
+    }
+}
+
+impl fmt::Display for Sessions {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut sessions: Vec<&Session> = self.sessions.iter().collect();
+        sessions.sort_by(|lhs, rhs| lhs.player.name().cmp(rhs.player.name()));
+
+        let sessions: Vec<String> = sessions.iter().map(|s| s.to_string()).collect();
+
+        write!(f, "{}", sessions.join(", "))
+    }
+}
+
+impl Sessions {
+    pub fn is_empty(&self) -> bool {
+        self.sessions.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Session> {
+        self.sessions.iter()
+    }
 }
 
 #[cfg(test)]
@@ -129,4 +258,29 @@ mod tests {
 
         assert_eq!(actual, expected);
     }
+
+    #[test]
+    fn test_players_union() {
+        let players_lhs = Players::from(vec![Player::new("idA", "A"), Player::new("idB", "B")]);
+        let players_rhs = Players::from(vec![Player::new("idB", "B"), Player::new("idC", "C")]);
+
+        let actual = &players_lhs | &players_rhs;
+        let expected = Players::from(vec![
+            Player::new("idA", "A"),
+            Player::new("idB", "B"),
+            Player::new("idC", "C"),
+        ]);
+
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_sessions_format() {
+        let sessions = Sessions::from(vec![
+            Session::new(Player::new("idB", "B"), Duration::seconds(45)),
+            Session::new(Player::new("idA", "A"), Duration::minutes(133)),
+            Session::new(Player::new("idC", "C"), Duration::minutes(5)),
+        ]);
+        assert_eq!(format!("{}", sessions), "A (2h13m), B (45s), C (5m)");
+    }
 }
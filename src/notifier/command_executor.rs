@@ -9,6 +9,8 @@ pub struct CommandExecutor {
     command: String,
     args: Vec<String>,
     pipe: bool,
+    /// writes the message in JSON instead of the text into stdin
+    json: bool,
 }
 
 impl CommandExecutor {
@@ -17,8 +19,27 @@ impl CommandExecutor {
             command: command.to_owned(),
             args,
             pipe,
+            json: false,
         }
     }
+
+    pub fn with_json(mut self, json: bool) -> Self {
+        self.json = json;
+        self
+    }
+
+    /// replaces `{msg}`, `{event}` and `{json}` in the arguments.
+    fn args(&self, message: &Message, json: &str) -> Vec<String> {
+        self.args
+            .iter()
+            .map(|arg| match arg.as_str() {
+                "{msg}" => message.body().clone(),
+                "{event}" => message.event().to_owned(),
+                "{json}" => json.to_owned(),
+                _ => arg.clone(),
+            })
+            .collect()
+    }
 }
 
 fn print_result(stdout: &[u8], stderr: &[u8]) -> Result<(), Error> {
@@ -33,11 +54,15 @@ fn print_result(stdout: &[u8], stderr: &[u8]) -> Result<(), Error> {
 }
 
 impl NotifierStrategy for CommandExecutor {
+    fn name(&self) -> &'static str {
+        "command"
+    }
+
     fn notify(&self, message: &Message) -> Result<(), Error> {
-        let modified_args = self
-            .args
-            .iter()
-            .map(|arg| if arg == "{msg}" { message.body() } else { arg });
+        let json = message
+            .to_json()
+            .map_err(|e| Error::FailedToPostMessage(e.to_string()))?;
+        let modified_args = self.args(message, &json);
 
         if self.pipe {
             let mut child = Command::new(&self.command)
@@ -50,13 +75,10 @@ impl NotifierStrategy for CommandExecutor {
             let child_stdin = child.stdin.as_mut().ok_or_else(|| {
                 Error::FailedToPostMessage("failed to open stdin of child process".to_string())
             })?;
-            child_stdin
-                .write_all(message.body().as_bytes())
-                .map_err(|_| {
-                    Error::FailedToPostMessage(
-                        "failed to write to stdin of child process".to_string(),
-                    )
-                })?;
+            let input = if self.json { &json } else { message.body() };
+            child_stdin.write_all(input.as_bytes()).map_err(|_| {
+                Error::FailedToPostMessage("failed to write to stdin of child process".to_string())
+            })?;
             #[allow(clippy::drop_ref)]
             drop(child_stdin); // Write EOF
 
@@ -79,3 +101,28 @@ impl NotifierStrategy for CommandExecutor {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_executor_replaces_args() {
+        let executor = CommandExecutor::new(
+            "notify-send",
+            vec![
+                "-c".to_owned(),
+                "{event}".to_owned(),
+                "{msg}".to_owned(),
+                "{json}".to_owned(),
+            ],
+            false,
+        );
+        let message = Message::new("A joined.").with_event("player_change");
+
+        assert_eq!(
+            executor.args(&message, "{}"),
+            vec!["-c", "player_change", "A joined.", "{}"]
+        );
+    }
+}
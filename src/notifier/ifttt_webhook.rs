@@ -8,33 +8,33 @@ use std::time::Duration;
 use tokio::time::timeout;
 
 use super::notifier_base::Error;
-use super::Message;
 use super::NotifierStrategy;
+use super::{Limit, Message};
 
 pub struct IFTTTWebhook {
     endpoint: String,
-    truncate: Option<usize>,
+    /// longer messages are split and posted as a series
+    max_length: Option<usize>,
 }
 
 impl IFTTTWebhook {
-    pub fn new(endpoint: &str, truncate: Option<usize>) -> Self {
+    pub fn new(endpoint: &str, max_length: Option<usize>) -> Self {
         Self {
             endpoint: endpoint.to_owned(),
-            truncate,
+            max_length,
         }
     }
-}
 
-impl NotifierStrategy for IFTTTWebhook {
-    fn notify(&self, message: &Message) -> Result<(), Error> {
-        let client = reqwest::Client::new();
-        let body = match self.truncate {
-            Some(len) => message.truncate(len).body().clone(),
-            None => message.body().clone(),
-        };
+    fn post(&self, client: &reqwest::Client, message: &Message) -> Result<(), Error> {
+        let json = message
+            .to_json()
+            .map_err(|e| Error::FailedToPostMessage(e.to_string()))?;
 
+        // value1: the text, value2: the kind of the event, value3: the whole message in JSON
         let mut params = HashMap::new();
-        params.insert("value1", body);
+        params.insert("value1", message.body().clone());
+        params.insert("value2", message.event().to_owned());
+        params.insert("value3", json);
 
         let future = client.post(&self.endpoint).form(&params).send();
 
@@ -49,3 +49,23 @@ impl NotifierStrategy for IFTTTWebhook {
         }
     }
 }
+
+impl NotifierStrategy for IFTTTWebhook {
+    fn name(&self) -> &'static str {
+        "ifttt"
+    }
+
+    fn notify(&self, message: &Message) -> Result<(), Error> {
+        let client = reqwest::Client::new();
+        let parts = match self.max_length {
+            Some(len) => message.split(&Limit::chars(len)),
+            None => vec![message.clone()],
+        };
+
+        for part in parts.iter() {
+            self.post(&client, part)?;
+        }
+
+        Ok(())
+    }
+}
@@ -1,34 +1,193 @@
-#[derive(Clone)]
+extern crate serde_json;
+
+use super::splitter::{self, Limit};
+use crate::models::{Player, Players};
+use crate::status_checker::ServerContext;
+use chrono::{DateTime, Utc};
+
+/// a notification: the rendered text and what it is about.
+/// the JSON serialization is a stable interface for scripts and webhooks, see `README.md`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
 pub struct Message {
+    /// the kind of the event, e.g. `player_change`.
+    /// `announce` for `mcnotify ctl announce`, and `held` for messages held during quiet hours.
+    event: String,
     body: String,
+    timestamp: DateTime<Utc>,
+    server: Option<ServerContext>,
+    joined_players: Vec<Player>,
+    left_players: Vec<Player>,
+    current_players: Vec<Player>,
+    online_count: Option<u32>,
+    /// the numbers of players who joined and left, known even if the server hides who they are
+    joined_count: Option<u32>,
+    left_count: Option<u32>,
 }
 
 impl Message {
     pub fn new(body: &str) -> Self {
         Self {
+            event: "message".to_owned(),
             body: body.to_owned(),
+            timestamp: Utc::now(),
+            server: None,
+            joined_players: Vec::new(),
+            left_players: Vec::new(),
+            current_players: Vec::new(),
+            online_count: None,
+            joined_count: None,
+            left_count: None,
         }
     }
 
+    pub fn with_event(mut self, event: &str) -> Self {
+        self.event = event.to_owned();
+        self
+    }
+
+    pub fn with_timestamp(mut self, timestamp: DateTime<Utc>) -> Self {
+        self.timestamp = timestamp;
+        self
+    }
+
+    pub fn with_server(mut self, server: &ServerContext) -> Self {
+        self.server = Some(server.clone());
+        self
+    }
+
+    /// the joined players and their number.
+    pub fn with_joined_players(mut self, players: &Players) -> Self {
+        self.joined_players = sorted(players);
+        self.joined_count = Some(self.joined_players.len() as u32);
+        self
+    }
+
+    /// the left players and their number.
+    pub fn with_left_players(mut self, players: &Players) -> Self {
+        self.left_players = sorted(players);
+        self.left_count = Some(self.left_players.len() as u32);
+        self
+    }
+
+    /// the online players, which may be fewer than `online_count`
+    /// if the server hides some of them.
+    pub fn with_current_players(mut self, players: &Players, online_count: u32) -> Self {
+        self.current_players = sorted(players);
+        self.online_count = Some(online_count);
+        self
+    }
+
+    pub fn with_online_count(mut self, online_count: u32) -> Self {
+        self.online_count = Some(online_count);
+        self
+    }
+
+    pub fn with_counts(mut self, joined_count: u32, left_count: u32) -> Self {
+        self.joined_count = Some(joined_count);
+        self.left_count = Some(left_count);
+        self
+    }
+
+    pub fn event(&self) -> &str {
+        &self.event
+    }
+
     pub fn body(&self) -> &String {
         &self.body
     }
 
-    pub fn truncate(&self, len: usize) -> Self {
-        let truncated_body: String = self.body.chars().take(len).collect();
-        Self {
-            body: truncated_body,
-        }
+    pub fn timestamp(&self) -> DateTime<Utc> {
+        self.timestamp
     }
+
+    pub fn server(&self) -> Option<&ServerContext> {
+        self.server.as_ref()
+    }
+
+    pub fn joined_players(&self) -> &[Player] {
+        &self.joined_players
+    }
+
+    pub fn left_players(&self) -> &[Player] {
+        &self.left_players
+    }
+
+    pub fn current_players(&self) -> &[Player] {
+        &self.current_players
+    }
+
+    pub fn online_count(&self) -> Option<u32> {
+        self.online_count
+    }
+
+    pub fn to_json(&self) -> Result<String, serde_json::Error> {
+        serde_json::to_string(self)
+    }
+
+    /// splits the body into numbered parts which fit in `limit`, keeping the rest as it is.
+    pub fn split(&self, limit: &Limit) -> Vec<Self> {
+        splitter::split(&self.body, limit)
+            .into_iter()
+            .map(|body| Self {
+                body,
+                ..self.clone()
+            })
+            .collect()
+    }
+}
+
+/// sorted by names, the same as `Players` is displayed.
+fn sorted(players: &Players) -> Vec<Player> {
+    let mut players: Vec<Player> = players.iter().cloned().collect();
+    players.sort_by(|lhs, rhs| lhs.name().cmp(rhs.name()));
+    players
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use chrono::TimeZone;
 
     #[test]
-    fn message_truncate() {
-        let message = Message::new("こんにちは、世界。").truncate(5);
-        assert_eq!(message.body(), "こんにちは");
+    fn message_split() {
+        let messages = Message::new("こんにちは、世界。\nお元気ですか？")
+            .with_event("announce")
+            .split(&Limit::chars(15));
+
+        assert_eq!(messages.len(), 2);
+        assert_eq!(messages[0].body(), "こんにちは、世界。 (1/2)");
+        assert_eq!(messages[1].body(), "お元気ですか？ (2/2)");
+        assert_eq!(messages[1].event(), "announce");
+    }
+
+    #[test]
+    fn message_to_json() {
+        let server = ServerContext {
+            hostname: "localhost".to_owned(),
+            port: 25565,
+            max: Some(20),
+            ..ServerContext::default()
+        };
+        let message = Message::new("B joined.")
+            .with_event("player_change")
+            .with_timestamp(Utc.ymd(2022, 6, 4).and_hms(15, 4, 0))
+            .with_server(&server)
+            .with_joined_players(&Players::from(vec![Player::new("idB", "B")]))
+            .with_left_players(&Players::default())
+            .with_current_players(
+                &Players::from(vec![Player::new("idB", "B"), Player::new("idA", "A")]),
+                2,
+            );
+
+        assert_eq!(
+            message.to_json().unwrap(),
+            concat!(
+                r#"{"event":"player_change","body":"B joined.","timestamp":"2022-06-04T15:04:00Z","#,
+                r#""server":{"hostname":"localhost","port":25565,"max":20,"version":null,"motd":null,"latency":null},"#,
+                r#""joined_players":[{"id":"idB","name":"B"}],"left_players":[],"#,
+                r#""current_players":[{"id":"idA","name":"A"},{"id":"idB","name":"B"}],"#,
+                r#""online_count":2,"joined_count":1,"left_count":0}"#
+            )
+        );
     }
 }
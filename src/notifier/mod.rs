@@ -1,5 +1,6 @@
 mod message;
 mod notifier_base;
+mod splitter;
 
 pub mod command_executor;
 pub mod ifttt_webhook;
@@ -8,3 +9,4 @@ pub mod twitter_eggmode;
 
 pub use self::message::Message;
 pub use self::notifier_base::{Error, NotifierStrategy};
+pub use self::splitter::{Limit, Weight};
@@ -6,6 +6,9 @@ pub enum Error {
 }
 
 pub trait NotifierStrategy {
+    /// the name of the notifier, which is the same as its section in the configuration
+    fn name(&self) -> &'static str;
+
     /// post a given message to the service
     fn notify(&self, message: &Message) -> Result<(), Error>;
 }
@@ -0,0 +1,201 @@
+/// how a platform counts the length of a text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weight {
+    /// every character counts one.
+    Chars,
+    /// the weighted counting of twitter-text: Latin letters and common punctuation count one,
+    /// and the others such as CJK characters and emoji count two.
+    Twitter,
+}
+
+/// the ranges of the code points which count one on Twitter.
+const TWITTER_LIGHT_RANGES: &[(u32, u32)] = &[
+    (0x0000, 0x10FF),
+    (0x2000, 0x200D),
+    (0x2010, 0x201F),
+    (0x2032, 0x2037),
+];
+
+impl Weight {
+    pub fn count(&self, text: &str) -> usize {
+        match *self {
+            Weight::Chars => text.chars().count(),
+            Weight::Twitter => text.chars().map(twitter_weight).sum(),
+        }
+    }
+}
+
+fn twitter_weight(c: char) -> usize {
+    let code = c as u32;
+    if TWITTER_LIGHT_RANGES
+        .iter()
+        .any(|&(start, end)| start <= code && code <= end)
+    {
+        1
+    } else {
+        2
+    }
+}
+
+/// the maximum length of a message on a platform.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limit {
+    pub max: usize,
+    pub weight: Weight,
+}
+
+impl Limit {
+    /// 280 in the weighted count, which is 140 in CJK characters.
+    pub const TWITTER: Limit = Limit {
+        max: 280,
+        weight: Weight::Twitter,
+    };
+
+    pub fn chars(max: usize) -> Self {
+        Self {
+            max,
+            weight: Weight::Chars,
+        }
+    }
+}
+
+/// the boundaries to split at, in the order of preference:
+/// lines, then players in a list, then words.
+const SEPARATORS: &[&str] = &["\n", ", ", " "];
+
+/// splits the text into numbered parts such as "... (1/3)" which fit in `limit`,
+/// breaking at lines and player names where possible.
+/// the text is returned as it is if it fits.
+pub fn split(text: &str, limit: &Limit) -> Vec<String> {
+    if limit.weight.count(text) <= limit.max {
+        return vec![text.to_owned()];
+    }
+
+    // the numbers take more room as the parts increase, e.g. " (10/12)"
+    let mut digits = 1;
+    loop {
+        let numbering = 4 + 2 * digits;
+        let room = limit.max.saturating_sub(numbering).max(1);
+        let parts = pack(text, room, limit.weight);
+
+        if parts.len().to_string().len() <= digits {
+            let total = parts.len();
+            return parts
+                .into_iter()
+                .enumerate()
+                .map(|(i, part)| format!("{} ({}/{})", part, i + 1, total))
+                .collect();
+        }
+        digits += 1;
+    }
+}
+
+/// packs the pieces between the separators into parts of `room` at most.
+fn pack(text: &str, room: usize, weight: Weight) -> Vec<String> {
+    pack_at(text, room, weight, 0)
+        .iter()
+        .map(|part| {
+            part.trim_start()
+                .trim_end_matches(|c: char| c == ',' || c.is_whitespace())
+                .to_owned()
+        })
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// a piece longer than `room` is broken at the next separator,
+/// and at characters as the last resort.
+fn pack_at(text: &str, room: usize, weight: Weight, level: usize) -> Vec<String> {
+    let pieces: Vec<&str> = match SEPARATORS.get(level) {
+        Some(separator) => text.split_inclusive(separator).collect(),
+        None => text
+            .char_indices()
+            .map(|(i, c)| &text[i..i + c.len_utf8()])
+            .collect(),
+    };
+
+    let mut parts = Vec::new();
+    let mut current = String::new();
+
+    for piece in pieces {
+        let piece_weight = weight.count(piece);
+        if piece_weight > room && level < SEPARATORS.len() {
+            if !current.is_empty() {
+                parts.push(std::mem::take(&mut current));
+            }
+            parts.extend(pack_at(piece, room, weight, level + 1));
+            continue;
+        }
+
+        if !current.is_empty() && weight.count(&current) + piece_weight > room {
+            parts.push(std::mem::take(&mut current));
+        }
+        current.push_str(piece);
+    }
+    if !current.is_empty() {
+        parts.push(current);
+    }
+
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn weight_counts_twitter_characters() {
+        assert_eq!(Weight::Chars.count("こんにちは"), 5);
+        assert_eq!(Weight::Twitter.count("こんにちは"), 10);
+        assert_eq!(Weight::Twitter.count("Hello, “world”"), 14);
+        assert_eq!(Weight::Twitter.count("Ünïcödé"), 7);
+    }
+
+    #[test]
+    fn split_keeps_short_text() {
+        assert_eq!(split("A joined.", &Limit::chars(10)), vec!["A joined."]);
+    }
+
+    #[test]
+    fn split_breaks_at_lines_and_names() {
+        let text = "[12:00]\nAlex, Steve, Notch joined.\nOnline: Alex, Steve, Notch, Herobrine";
+
+        assert_eq!(
+            split(text, &Limit::chars(50)),
+            vec![
+                "[12:00]\nAlex, Steve, Notch joined. (1/2)",
+                "Online: Alex, Steve, Notch, Herobrine (2/2)",
+            ]
+        );
+        assert_eq!(
+            split(text, &Limit::chars(30)),
+            vec![
+                "[12:00] (1/5)",
+                "Alex, Steve (2/5)",
+                "Notch joined. (3/5)",
+                "Online: Alex, Steve (4/5)",
+                "Notch, Herobrine (5/5)",
+            ]
+        );
+    }
+
+    #[test]
+    fn split_breaks_long_words() {
+        let parts = split(&"あ".repeat(300), &Limit::TWITTER);
+
+        assert_eq!(parts.len(), 3);
+        assert!(parts
+            .iter()
+            .all(|part| Weight::Twitter.count(part) <= Limit::TWITTER.max));
+        assert!(parts[2].ends_with(" (3/3)"));
+    }
+
+    #[test]
+    fn split_widens_numbering() {
+        let parts = split(&"ab ".repeat(40), &Limit::chars(10));
+
+        assert!(parts.len() >= 10);
+        assert!(parts.iter().all(|part| part.chars().count() <= 10));
+        assert!(parts[0].ends_with(&format!("(1/{})", parts.len())));
+    }
+}
@@ -17,6 +17,10 @@ impl Default for StdoutPrinter {
 }
 
 impl NotifierStrategy for StdoutPrinter {
+    fn name(&self) -> &'static str {
+        "stdout"
+    }
+
     fn notify(&self, message: &Message) -> Result<(), Error> {
         println!("{}", message.body());
 
@@ -4,8 +4,8 @@ extern crate futures;
 use self::futures::executor::block_on;
 
 use super::notifier_base::Error;
-use super::Message;
 use super::NotifierStrategy;
+use super::{Limit, Message};
 
 pub struct TwitterEggMode {
     token: egg_mode::Token,
@@ -28,10 +28,24 @@ impl TwitterEggMode {
 }
 
 impl NotifierStrategy for TwitterEggMode {
+    fn name(&self) -> &'static str {
+        "twitter"
+    }
+
+    /// a long message is posted as a thread, each part replying to the previous one.
     fn notify(&self, message: &Message) -> Result<(), Error> {
-        let truncated = message.truncate(140);
-        block_on(egg_mode::tweet::DraftTweet::new(truncated.body().clone()).send(&self.token))
-            .map_err(|e| Error::FailedToPostMessage(e.to_string()))?;
+        let mut in_reply_to = None;
+
+        for part in message.split(&Limit::TWITTER) {
+            let mut draft = egg_mode::tweet::DraftTweet::new(part.body().clone());
+            if let Some(id) = in_reply_to {
+                draft = draft.in_reply_to(id);
+            }
+
+            let tweet = block_on(draft.send(&self.token))
+                .map_err(|e| Error::FailedToPostMessage(e.to_string()))?;
+            in_reply_to = Some(tweet.id);
+        }
 
         Ok(())
     }
@@ -0,0 +1,5 @@
+mod rules;
+mod window;
+
+pub use self::rules::{QuietAction, QuietHours, QuietRule};
+pub use self::window::TimeWindow;
@@ -0,0 +1,208 @@
+use super::TimeWindow;
+use crate::timezone::Timezone;
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+/// what happens to messages in a quiet window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QuietAction {
+    /// the messages are never sent.
+    Drop,
+    /// the messages are sent together when the window ends.
+    Hold,
+}
+
+impl FromStr for QuietAction {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "drop" => Ok(QuietAction::Drop),
+            "hold" => Ok(QuietAction::Hold),
+            _ => Err(format!("unknown action: {}", s)),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct QuietRule {
+    pub window: TimeWindow,
+    /// the kinds of events which are kept quiet. every event except `down` if empty.
+    pub events: Vec<String>,
+    /// the names of notifiers which are kept quiet. every notifier if empty.
+    pub notifiers: Vec<String>,
+    pub action: QuietAction,
+}
+
+impl QuietRule {
+    fn applies_to(&self, event: &str, notifier: &str) -> bool {
+        let event = if self.events.is_empty() {
+            event != "down"
+        } else {
+            self.events.iter().any(|e| e == event)
+        };
+        let notifier = self.notifiers.is_empty() || self.notifiers.iter().any(|n| n == notifier);
+
+        event && notifier
+    }
+}
+
+/// decides whether messages are sent right now, and keeps the held ones.
+#[derive(Default)]
+pub struct QuietHours {
+    rules: Vec<QuietRule>,
+    timezone: Timezone,
+    /// messages held by the rule of the index, for each notifier
+    held: BTreeMap<(usize, String), Vec<String>>,
+}
+
+impl QuietHours {
+    pub fn new(rules: Vec<QuietRule>, timezone: Timezone) -> Self {
+        Self {
+            rules,
+            timezone,
+            held: BTreeMap::new(),
+        }
+    }
+
+    /// returns the message if it should be sent right now. `Drop` wins over `Hold`.
+    pub fn filter(
+        &mut self,
+        notifier: &str,
+        event: &str,
+        message: &str,
+        now: DateTime<Utc>,
+    ) -> Option<String> {
+        let local = self.timezone.to_local(now);
+        let active: Vec<(usize, &QuietRule)> = self
+            .rules
+            .iter()
+            .enumerate()
+            .filter(|(_, rule)| rule.applies_to(event, notifier) && rule.window.contains(&local))
+            .collect();
+
+        if active
+            .iter()
+            .any(|(_, rule)| rule.action == QuietAction::Drop)
+        {
+            return None;
+        }
+
+        match active.first() {
+            Some(&(i, _)) => {
+                self.held
+                    .entry((i, notifier.to_owned()))
+                    .or_insert_with(Vec::new)
+                    .push(message.to_owned());
+                None
+            }
+            None => Some(message.to_owned()),
+        }
+    }
+
+    /// takes the messages held by the windows which have ended,
+    /// as a batched message for each notifier.
+    pub fn release(&mut self, now: DateTime<Utc>) -> Vec<(String, String)> {
+        let local = self.timezone.to_local(now);
+        let rules = &self.rules;
+        let (ended, held): (BTreeMap<_, _>, BTreeMap<_, _>) = std::mem::take(&mut self.held)
+            .into_iter()
+            .partition(|((i, _), _)| !rules[*i].window.contains(&local));
+        self.held = held;
+
+        ended
+            .into_iter()
+            .map(|((_, notifier), messages)| (notifier, messages.join("\n\n")))
+            .collect()
+    }
+
+    /// takes every held message regardless of the windows, e.g. before stopping.
+    pub fn take_all(&mut self) -> Vec<(String, String)> {
+        std::mem::take(&mut self.held)
+            .into_iter()
+            .map(|((_, notifier), messages)| (notifier, messages.join("\n\n")))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timezone::Timezone;
+    use chrono::TimeZone;
+
+    fn setup_quiet_hours() -> QuietHours {
+        let timezone: Timezone = "UTC".parse().unwrap();
+        QuietHours::new(
+            vec![
+                QuietRule {
+                    window: TimeWindow::new("23:00", "07:00", &[]).unwrap(),
+                    events: Vec::new(),
+                    notifiers: vec!["twitter".to_owned()],
+                    action: QuietAction::Hold,
+                },
+                QuietRule {
+                    window: TimeWindow::new("00:00", "06:00", &[]).unwrap(),
+                    events: vec!["motd_change".to_owned()],
+                    notifiers: Vec::new(),
+                    action: QuietAction::Drop,
+                },
+            ],
+            timezone,
+        )
+    }
+
+    #[test]
+    fn quiet_hours_hold_and_release() {
+        let mut quiet_hours = setup_quiet_hours();
+        let night = Utc.ymd(2022, 7, 1).and_hms(3, 0, 0);
+        let morning = Utc.ymd(2022, 7, 1).and_hms(7, 0, 0);
+
+        assert_eq!(
+            quiet_hours.filter("stdout", "player_change", "A joined", night),
+            Some("A joined".to_owned())
+        );
+        assert_eq!(
+            quiet_hours.filter("twitter", "player_change", "A joined", night),
+            None
+        );
+        assert_eq!(
+            quiet_hours.filter("twitter", "down", "down", night),
+            Some("down".to_owned())
+        );
+        quiet_hours.filter("twitter", "player_change", "B joined", night);
+
+        assert!(quiet_hours.release(night).is_empty());
+        assert_eq!(
+            quiet_hours.release(morning),
+            vec![("twitter".to_owned(), "A joined\n\nB joined".to_owned())]
+        );
+        assert!(quiet_hours.release(morning).is_empty());
+
+        quiet_hours.filter("twitter", "player_change", "C joined", night);
+        assert_eq!(
+            quiet_hours.take_all(),
+            vec![("twitter".to_owned(), "C joined".to_owned())]
+        );
+        assert!(quiet_hours.release(morning).is_empty());
+    }
+
+    #[test]
+    fn quiet_hours_drop() {
+        let mut quiet_hours = setup_quiet_hours();
+        let night = Utc.ymd(2022, 7, 1).and_hms(3, 0, 0);
+
+        assert_eq!(
+            quiet_hours.filter("twitter", "motd_change", "MOTD", night),
+            None
+        );
+        assert_eq!(
+            quiet_hours.filter("stdout", "motd_change", "MOTD", night),
+            None
+        );
+        assert!(quiet_hours
+            .release(Utc.ymd(2022, 7, 1).and_hms(12, 0, 0))
+            .is_empty());
+    }
+}
@@ -0,0 +1,89 @@
+use chrono::{Datelike, Duration, NaiveDateTime, NaiveTime, Weekday};
+use std::cmp::Ordering;
+
+/// a period of every day, such as 23:00 - 07:00.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TimeWindow {
+    start: NaiveTime,
+    end: NaiveTime,
+    /// the days on which the window starts. every day if empty.
+    days: Vec<Weekday>,
+}
+
+impl TimeWindow {
+    /// `start` and `end` are `HH:MM`. the window ends on the next day if `end` is earlier than `start`,
+    /// and lasts all day if they are the same.
+    pub fn new(start: &str, end: &str, days: &[String]) -> Result<Self, String> {
+        let days = days
+            .iter()
+            .map(|day| Self::parse_day(day))
+            .collect::<Result<Vec<Weekday>, String>>()?;
+
+        Ok(Self {
+            start: Self::parse_time(start)?,
+            end: Self::parse_time(end)?,
+            days,
+        })
+    }
+
+    /// `HH:MM`
+    pub fn parse_time(time: &str) -> Result<NaiveTime, String> {
+        NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| format!("invalid time: {}", time))
+    }
+
+    /// `mon`, `tue`, ...
+    pub fn parse_day(day: &str) -> Result<Weekday, String> {
+        day.parse().map_err(|_| format!("invalid day: {}", day))
+    }
+
+    fn starts_on(&self, datetime: &NaiveDateTime) -> bool {
+        self.days.is_empty() || self.days.contains(&datetime.weekday())
+    }
+
+    pub fn contains(&self, datetime: &NaiveDateTime) -> bool {
+        let time = datetime.time();
+
+        match self.start.cmp(&self.end) {
+            Ordering::Less => self.starts_on(datetime) && self.start <= time && time < self.end,
+            Ordering::Greater => {
+                (self.start <= time && self.starts_on(datetime))
+                    || (time < self.end && self.starts_on(&(*datetime - Duration::days(1))))
+            }
+            Ordering::Equal => self.starts_on(datetime),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn datetime(d: u32, h: u32, min: u32) -> NaiveDateTime {
+        // 2022-07-01 is Friday
+        NaiveDate::from_ymd(2022, 7, d).and_hms(h, min, 0)
+    }
+
+    #[test]
+    fn time_window_over_midnight() {
+        let window = TimeWindow::new("23:00", "07:00", &[]).unwrap();
+
+        assert!(window.contains(&datetime(1, 23, 0)));
+        assert!(window.contains(&datetime(2, 3, 0)));
+        assert!(!window.contains(&datetime(2, 7, 0)));
+        assert!(!window.contains(&datetime(2, 12, 0)));
+    }
+
+    #[test]
+    fn time_window_on_days() {
+        let window = TimeWindow::new("22:00", "02:00", &["fri".to_owned()]).unwrap();
+
+        assert!(window.contains(&datetime(1, 23, 0)));
+        assert!(window.contains(&datetime(2, 1, 0)));
+        assert!(!window.contains(&datetime(2, 23, 0)));
+        assert!(!window.contains(&datetime(1, 1, 0)));
+
+        assert!(TimeWindow::new("25:00", "02:00", &[]).is_err());
+        assert!(TimeWindow::new("22:00", "02:00", &["someday".to_owned()]).is_err());
+    }
+}
@@ -0,0 +1,88 @@
+extern crate signal_hook;
+
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use signal_hook::flag;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// how often the flags are looked at while sleeping.
+const SLEEP_STEP: Duration = Duration::from_millis(100);
+
+/// the requests which arrived as signals: SIGTERM / SIGINT to stop, and SIGHUP to reload.
+#[derive(Default)]
+pub struct Signals {
+    terminate: Arc<AtomicBool>,
+    reload: Arc<AtomicBool>,
+}
+
+impl Signals {
+    pub fn register() -> io::Result<Self> {
+        let signals = Self::default();
+
+        for signal in [SIGTERM, SIGINT] {
+            // the second signal kills the process without waiting for the current check
+            flag::register_conditional_shutdown(signal, 1, Arc::clone(&signals.terminate))?;
+            flag::register(signal, Arc::clone(&signals.terminate))?;
+        }
+        flag::register(SIGHUP, Arc::clone(&signals.reload))?;
+
+        Ok(signals)
+    }
+
+    pub fn terminate_requested(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed)
+    }
+
+    /// true if either of the requests has arrived.
+    pub fn pending(&self) -> bool {
+        self.terminate.load(Ordering::Relaxed) || self.reload.load(Ordering::Relaxed)
+    }
+
+    /// returns true once for each reload request.
+    pub fn take_reload(&self) -> bool {
+        self.reload.swap(false, Ordering::Relaxed)
+    }
+
+    /// sleeps for `duration`, or until a signal arrives.
+    pub fn sleep(&self, duration: Duration) {
+        let until = Instant::now() + duration;
+
+        loop {
+            if self.pending() {
+                return;
+            }
+
+            let now = Instant::now();
+            if now >= until {
+                return;
+            }
+            thread::sleep(SLEEP_STEP.min(until - now));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signals_interrupt_sleep() {
+        let signals = Signals::default();
+
+        let started_at = Instant::now();
+        signals.sleep(Duration::from_millis(50));
+        assert!(started_at.elapsed() >= Duration::from_millis(50));
+
+        signals.reload.store(true, Ordering::Relaxed);
+        let started_at = Instant::now();
+        signals.sleep(Duration::from_secs(60));
+        assert!(started_at.elapsed() < Duration::from_secs(1));
+
+        assert!(signals.take_reload());
+        assert!(!signals.take_reload());
+        assert!(!signals.pending());
+    }
+}
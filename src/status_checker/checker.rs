@@ -1,12 +1,25 @@
+use super::count_triggers::{CountRules, CountTriggers};
+use super::formats::ServerContext;
+use super::player_registry::PlayerRegistry;
+use super::session_tracker::SessionTracker;
+use super::state_file::CheckerState;
 use crate::minecraft::{client, packet};
-use crate::models::Players;
+use crate::models::{Players, ServerInfo, Sessions};
+use chrono::{DateTime, Utc};
+use std::time::{Duration, Instant};
 
 /// represents a server state.
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub enum Status {
     Available {
         online_count: u32,
         current_players: Players,
+        /// false if the server hides some of online players from `current_players`.
+        sample_complete: bool,
+        server_info: ServerInfo,
+        /// time taken to get the status
+        #[serde(default)]
+        latency: Duration,
     },
     Unavailable {
         reason: String,
@@ -20,14 +33,62 @@ pub enum StatusDifference {
         current_players: Players,
         joined_players: Players,
         left_players: Players,
+        /// sessions of `left_players`
+        finished_sessions: Sessions,
+    },
+    /// the number of players changed, but who joined or left is unknown
+    /// because the server doesn't send the complete player list
+    PlayerCountChange {
+        online_count: u32,
+        current_players: Players,
+        joined_count: u32,
+        left_count: u32,
     },
     /// recovered from
     Recover {
         online_count: u32,
         current_players: Players,
+        /// how long the server was down, unknown if it was down on start
+        downtime: Option<chrono::Duration>,
     },
     Down {
         reason: String,
+        /// sessions of the players who were online when the server went down
+        finished_sessions: Sessions,
+    },
+    /// the description (MOTD) of the server changed
+    MotdChange {
+        old_motd: String,
+        motd: String,
+    },
+    /// the version name or the protocol version of the server changed
+    VersionChange {
+        old_version: String,
+        version: String,
+        old_protocol: u32,
+        protocol: u32,
+    },
+    /// the maximum number of players changed
+    MaxPlayersChange {
+        old_max: u32,
+        max: u32,
+    },
+    /// the number of online players reached a configured threshold
+    PlayerCountReached {
+        threshold: u32,
+        online_count: u32,
+        max: u32,
+    },
+    /// the number of online players reached the maximum
+    ServerFull {
+        online_count: u32,
+        max: u32,
+    },
+    /// all players left the server
+    ServerEmpty,
+    /// players who have never been seen on the server joined
+    NewPlayer {
+        players: Players,
     },
     None {
         latest_status: Status,
@@ -35,6 +96,26 @@ pub enum StatusDifference {
 }
 
 impl StatusDifference {
+    /// the name of the event, which is used to configure rules per event.
+    pub fn kind(&self) -> &'static str {
+        use self::StatusDifference::*;
+
+        match *self {
+            PlayerChange { .. } => "player_change",
+            PlayerCountChange { .. } => "player_count_change",
+            Recover { .. } => "recover",
+            Down { .. } => "down",
+            MotdChange { .. } => "motd_change",
+            VersionChange { .. } => "version_change",
+            MaxPlayersChange { .. } => "max_players_change",
+            PlayerCountReached { .. } => "count_reached",
+            ServerFull { .. } => "full",
+            ServerEmpty => "empty",
+            NewPlayer { .. } => "new_player",
+            None { .. } => "none",
+        }
+    }
+
     fn from_between(latest_status: &Status, current_status: &Status) -> Self {
         use self::Status::*;
         use self::StatusDifference::*;
@@ -53,10 +134,49 @@ impl StatusDifference {
             ) => Recover {
                 online_count,
                 current_players: current_players.clone(),
+                downtime: Option::None,
             },
             (&Available { .. }, &Unavailable { ref reason }) => Down {
                 reason: reason.clone(),
+                finished_sessions: Sessions::default(),
             },
+            (
+                &Available {
+                    online_count: latest_count,
+                    sample_complete: false,
+                    ..
+                },
+                &Available {
+                    online_count,
+                    ref current_players,
+                    ..
+                },
+            )
+            | (
+                &Available {
+                    online_count: latest_count,
+                    ..
+                },
+                &Available {
+                    online_count,
+                    ref current_players,
+                    sample_complete: false,
+                    ..
+                },
+            ) => {
+                if online_count != latest_count {
+                    PlayerCountChange {
+                        online_count,
+                        current_players: current_players.clone(),
+                        joined_count: online_count.saturating_sub(latest_count),
+                        left_count: latest_count.saturating_sub(online_count),
+                    }
+                } else {
+                    None {
+                        latest_status: latest_status.clone(),
+                    }
+                }
+            }
             (
                 &Available {
                     current_players: ref latest_players,
@@ -65,6 +185,7 @@ impl StatusDifference {
                 &Available {
                     online_count,
                     ref current_players,
+                    ..
                 },
             ) => {
                 let joined_players = current_players - latest_players;
@@ -76,6 +197,7 @@ impl StatusDifference {
                         current_players: current_players.clone(),
                         joined_players,
                         left_players,
+                        finished_sessions: Sessions::default(),
                     }
                 } else {
                     None {
@@ -87,27 +209,417 @@ impl StatusDifference {
     }
 }
 
+/// decides how long a transition has to be sustained before it is announced.
+#[derive(Clone, Copy, Debug)]
+pub struct Thresholds {
+    /// the number of consecutive failed checks required to announce `Down`.
+    pub down: u32,
+    /// the number of consecutive successful checks required to announce `Recover`.
+    pub recover: u32,
+    /// the server has to be unreachable at least this long to announce `Down`.
+    pub grace_period: Duration,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        Self {
+            down: 1,
+            recover: 1,
+            grace_period: Duration::from_secs(0),
+        }
+    }
+}
+
+/// represents a transition which is observed but not announced yet.
+enum Transition {
+    Settled,
+    Pending { count: u32, since: DateTime<Utc> },
+}
+
 pub struct StatusChecker {
     hostname: String,
     port: u16,
+    thresholds: Thresholds,
+    /// the status which notifiers were told about most recently.
+    announced_status: Status,
+    /// the status got by the latest check, which may not be announced yet.
     latest_status: Status,
+    transition: Transition,
+    sessions: SessionTracker,
+    /// kept while the server is down to detect changes across a restart.
+    last_server_info: Option<ServerInfo>,
+    /// when the server went down, to tell the downtime on recovery.
+    down_since: Option<DateTime<Utc>>,
+    count_triggers: CountTriggers,
+    registry: Option<PlayerRegistry>,
+    /// a player who comes back within this is not announced to have left nor joined.
+    rejoin_grace: chrono::Duration,
 }
 
 impl StatusChecker {
-    pub fn new(hostname: &str, port: u16) -> Self {
+    pub fn new(hostname: &str, port: u16, thresholds: Thresholds) -> Self {
         Self {
             hostname: hostname.to_owned(),
             port,
+            thresholds,
+            announced_status: Status::Unavailable {
+                reason: "on start".to_owned(),
+            },
             latest_status: Status::Unavailable {
                 reason: "on start".to_owned(),
             },
+            transition: Transition::Settled,
+            sessions: SessionTracker::default(),
+            last_server_info: Option::None,
+            down_since: Option::None,
+            count_triggers: CountTriggers::new(CountRules::default()),
+            registry: Option::None,
+            rejoin_grace: chrono::Duration::zero(),
+        }
+    }
+
+    pub fn with_registry(mut self, registry: PlayerRegistry) -> Self {
+        self.registry = Some(registry);
+        self
+    }
+
+    pub fn with_count_rules(mut self, rules: CountRules) -> Self {
+        self.count_triggers = CountTriggers::new(rules);
+        self
+    }
+
+    /// a grace of zero announces every join and leave.
+    pub fn with_rejoin_grace(mut self, grace: chrono::Duration) -> Self {
+        self.rejoin_grace = grace;
+        self
+    }
+
+    /// takes a snapshot of the announced status to restore it after a restart.
+    pub fn state(&self, now: DateTime<Utc>) -> CheckerState {
+        CheckerState {
+            saved_at: now,
+            status: self.announced_status.clone(),
+            sessions: self.sessions.snapshot(),
+            server_info: self.last_server_info.clone(),
+            down_since: self.down_since,
+            away: self.sessions.away_snapshot(),
+        }
+    }
+
+    pub fn restore(&mut self, state: CheckerState) {
+        self.announced_status = state.status;
+        self.transition = Transition::Settled;
+        self.sessions = SessionTracker::from(state.sessions).with_away(state.away);
+        self.last_server_info = state.server_info;
+        self.down_since = state.down_since;
+    }
+
+    /// the variables about the server for the message formats.
+    pub fn server_context(&self) -> ServerContext {
+        let info = self.last_server_info.as_ref();
+        let latency = match self.latest_status {
+            Status::Available { latency, .. } => Some(latency.as_millis() as u64),
+            Status::Unavailable { .. } => Option::None,
+        };
+
+        ServerContext {
+            hostname: self.hostname.clone(),
+            port: self.port,
+            max: info.map(ServerInfo::max_players),
+            version: info.map(|info| info.version().clone()),
+            motd: info.map(|info| info.motd().clone()),
+            latency,
         }
     }
 
-    pub fn get_status_difference(&mut self) -> StatusDifference {
+    /// returns the transition of the server state first, followed by the players who didn't come
+    /// back within the rejoin grace period, and changes of the server info.
+    pub fn get_status_differences(&mut self) -> Vec<StatusDifference> {
         let current_status = self.get_status();
-        let difference = StatusDifference::from_between(&self.latest_status, &current_status);
-        self.latest_status = current_status;
+        self.check(current_status, Utc::now())
+    }
+
+    /// forgets the players in the registry, so they are welcomed on their next visit.
+    pub fn forget_new_players(&mut self, players: &Players) {
+        let registry = match self.registry {
+            Some(ref mut registry) if !players.is_empty() => registry,
+            _ => return,
+        };
+
+        registry.forget(players);
+        if let Err(e) = registry.save() {
+            warn!("Couldn't save the player registry: {:?}", e);
+        }
+    }
+
+    pub fn latest_status(&self) -> &Status {
+        &self.latest_status
+    }
+
+    fn check(&mut self, current_status: Status, now: DateTime<Utc>) -> Vec<StatusDifference> {
+        self.latest_status = current_status.clone();
+
+        let difference = self.advance(current_status, now);
+        let new_players = self.detect_new_players(&difference, now);
+        let expired = self.expire_rejoins(now);
+
+        // the players who left within the grace period are counted out when they expire
+        let mut count_events = self.detect_count_events(&difference);
+        if let Some(ref expired) = expired {
+            count_events.extend(self.detect_count_events(expired));
+        }
+
+        let mut differences = vec![difference];
+        differences.extend(expired);
+        differences.extend(new_players);
+        differences.extend(self.detect_server_info_changes());
+        differences.extend(count_events);
+
+        differences
+    }
+
+    /// announces the players who have been away longer than the rejoin grace period,
+    /// with the sessions finished when they left.
+    fn expire_rejoins(&mut self, now: DateTime<Utc>) -> Option<StatusDifference> {
+        let (online_count, current_players) = match self.announced_status {
+            Status::Available {
+                online_count,
+                ref current_players,
+                ..
+            } => (online_count, current_players.clone()),
+            Status::Unavailable { .. } => return Option::None,
+        };
+
+        let (left_players, finished_sessions) = self.sessions.expire(self.rejoin_grace, now);
+        if left_players.is_empty() {
+            return Option::None;
+        }
+
+        Some(StatusDifference::PlayerChange {
+            online_count,
+            current_players,
+            joined_players: Players::default(),
+            left_players,
+            finished_sessions,
+        })
+    }
+
+    fn detect_new_players(
+        &mut self,
+        difference: &StatusDifference,
+        now: DateTime<Utc>,
+    ) -> Option<StatusDifference> {
+        use self::StatusDifference::*;
+
+        let registry = self.registry.as_mut()?;
+
+        let current_players = match *difference {
+            PlayerChange {
+                ref current_players,
+                ..
+            }
+            | PlayerCountChange {
+                ref current_players,
+                ..
+            }
+            | Recover {
+                ref current_players,
+                ..
+            } => current_players,
+            _ => return Option::None,
+        };
+
+        let players = registry.record(current_players, now);
+        if let Err(e) = registry.save() {
+            warn!("Couldn't save the player registry: {:?}", e);
+        }
+
+        if players.is_empty() {
+            Option::None
+        } else {
+            Some(NewPlayer { players })
+        }
+    }
+
+    fn detect_count_events(&mut self, difference: &StatusDifference) -> Vec<StatusDifference> {
+        use self::StatusDifference::*;
+
+        let (online_count, max) = match self.announced_status {
+            Status::Available {
+                online_count,
+                ref server_info,
+                ..
+            } => (online_count, server_info.max_players()),
+            Status::Unavailable { .. } => (0, 0),
+        };
+
+        match *difference {
+            PlayerChange { .. } | PlayerCountChange { .. } => {
+                self.count_triggers.update(online_count, max)
+            }
+            Recover { .. } => {
+                // not to announce the players who were online before the server went down
+                self.count_triggers.reset();
+                self.count_triggers.update(online_count, max)
+            }
+            Down { .. } => {
+                self.count_triggers.reset();
+                Vec::new()
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn detect_server_info_changes(&mut self) -> Vec<StatusDifference> {
+        use self::StatusDifference::*;
+
+        let server_info = match self.announced_status {
+            Status::Available {
+                ref server_info, ..
+            } => server_info.clone(),
+            Status::Unavailable { .. } => return Vec::new(),
+        };
+
+        let old_info = match self.last_server_info.replace(server_info.clone()) {
+            Some(old_info) => old_info,
+            Option::None => return Vec::new(),
+        };
+
+        let mut differences = Vec::new();
+
+        if old_info.motd() != server_info.motd() {
+            differences.push(MotdChange {
+                old_motd: old_info.motd().clone(),
+                motd: server_info.motd().clone(),
+            });
+        }
+
+        if old_info.version() != server_info.version()
+            || old_info.protocol() != server_info.protocol()
+        {
+            differences.push(VersionChange {
+                old_version: old_info.version().clone(),
+                version: server_info.version().clone(),
+                old_protocol: old_info.protocol(),
+                protocol: server_info.protocol(),
+            });
+        }
+
+        if old_info.max_players() != server_info.max_players() {
+            differences.push(MaxPlayersChange {
+                old_max: old_info.max_players(),
+                max: server_info.max_players(),
+            });
+        }
+
+        differences
+    }
+
+    fn advance(&mut self, current_status: Status, now: DateTime<Utc>) -> StatusDifference {
+        use self::Status::*;
+
+        let (count, since) = match (&self.announced_status, &current_status) {
+            (&Available { .. }, &Available { .. }) | (&Unavailable { .. }, &Unavailable { .. }) => {
+                self.transition = Transition::Settled;
+                return self.announce(current_status, now);
+            }
+            _ => match self.transition {
+                Transition::Settled => (1, now),
+                Transition::Pending { count, since } => (count + 1, since),
+            },
+        };
+
+        let sustained = match current_status {
+            Available { .. } => count >= self.thresholds.recover,
+            Unavailable { .. } => {
+                count >= self.thresholds.down
+                    && (now - since).to_std().unwrap_or_default() >= self.thresholds.grace_period
+            }
+        };
+
+        if sustained {
+            self.transition = Transition::Settled;
+            self.announce(current_status, since)
+        } else {
+            self.transition = Transition::Pending { count, since };
+            StatusDifference::None {
+                latest_status: current_status,
+            }
+        }
+    }
+
+    /// `at` is when the server actually reached `current_status`.
+    fn announce(&mut self, current_status: Status, at: DateTime<Utc>) -> StatusDifference {
+        use self::StatusDifference::*;
+
+        let mut difference =
+            StatusDifference::from_between(&self.announced_status, &current_status);
+
+        // a truncated sample rotates, so nobody's session can be told from it
+        let sample_complete = match current_status {
+            Status::Available {
+                sample_complete, ..
+            } => sample_complete,
+            Status::Unavailable { .. } => true,
+        };
+        if !sample_complete {
+            self.sessions.forget_active();
+        }
+        self.announced_status = current_status;
+
+        match difference {
+            PlayerChange {
+                ref mut joined_players,
+                ref mut left_players,
+                ..
+            } if self.rejoin_grace > chrono::Duration::zero() => {
+                // the players who left are announced later by `expire_rejoins` unless they come back
+                let rejoined = self.sessions.resume(joined_players);
+                *joined_players = &*joined_players - &rejoined;
+                self.sessions.start(joined_players, at);
+                self.sessions.leave(left_players, at);
+                *left_players = Players::default();
+
+                if joined_players.is_empty() {
+                    return None {
+                        latest_status: self.announced_status.clone(),
+                    };
+                }
+            }
+            PlayerChange {
+                ref joined_players,
+                ref left_players,
+                ref mut finished_sessions,
+                ..
+            } => {
+                self.sessions.start(joined_players, at);
+                *finished_sessions = self.sessions.finish(left_players, at);
+            }
+            Recover {
+                ref current_players,
+                ref mut downtime,
+                ..
+            } => {
+                if sample_complete {
+                    self.sessions.start(current_players, at);
+                }
+                *downtime = self.down_since.take().map(|since| at - since);
+            }
+            PlayerCountChange {
+                ref current_players,
+                ..
+            } if sample_complete => {
+                self.sessions.start(current_players, at);
+            }
+            Down {
+                ref mut finished_sessions,
+                ..
+            } => {
+                *finished_sessions = self.sessions.finish_all(at);
+                self.down_since = Some(at);
+            }
+            _ => {}
+        }
 
         difference
     }
@@ -116,6 +628,7 @@ impl StatusChecker {
         use self::Status::*;
 
         let address = client::ServerAddr::new(&self.hostname, self.port);
+        let started_at = Instant::now();
 
         // get status
         let mut cli = match client::Client::connect(address) {
@@ -146,12 +659,532 @@ impl StatusChecker {
         };
 
         // build information
+        let server_info = ServerInfo::new(
+            &status.description.to_plain_text(),
+            &status.version.name,
+            status.version.protocol,
+            status.players.max,
+        );
         let online_count = status.players.online;
-        let current_players = Players::from(status.players.sample.unwrap_or_default());
+        let sample = status.players.sample.unwrap_or_default();
+        let sample_complete = sample.len() as u32 >= online_count;
+        let current_players = Players::from(sample);
 
         Status::Available {
             online_count,
             current_players,
+            sample_complete,
+            server_info,
+            latency: started_at.elapsed(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Player, Session};
+
+    fn available(names: &[&str]) -> Status {
+        let players: Vec<Player> = names.iter().map(|name| Player::new(name, name)).collect();
+        Status::Available {
+            online_count: players.len() as u32,
+            current_players: Players::from(players),
+            sample_complete: true,
+            server_info: ServerInfo::default(),
+            latency: Duration::default(),
+        }
+    }
+
+    fn truncated(online_count: u32, names: &[&str]) -> Status {
+        let players: Vec<Player> = names.iter().map(|name| Player::new(name, name)).collect();
+        Status::Available {
+            online_count,
+            current_players: Players::from(players),
+            sample_complete: false,
+            server_info: ServerInfo::default(),
+            latency: Duration::default(),
+        }
+    }
+
+    fn with_server_info(server_info: ServerInfo) -> Status {
+        Status::Available {
+            online_count: 0,
+            current_players: Players::default(),
+            sample_complete: true,
+            server_info,
+            latency: Duration::default(),
+        }
+    }
+
+    fn unavailable() -> Status {
+        Status::Unavailable {
+            reason: "timeout".to_owned(),
+        }
+    }
+
+    fn setup_checker(down: u32, recover: u32, grace_period: u64) -> StatusChecker {
+        StatusChecker::new(
+            "localhost",
+            25565,
+            Thresholds {
+                down,
+                recover,
+                grace_period: Duration::from_secs(grace_period),
+            },
+        )
+    }
+
+    #[test]
+    fn checker_announces_immediately_by_default() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let now = Utc::now();
+
+        assert!(matches!(
+            checker.advance(available(&[]), now),
+            StatusDifference::Recover { .. }
+        ));
+        assert!(matches!(
+            checker.advance(unavailable(), now),
+            StatusDifference::Down { .. }
+        ));
+    }
+
+    #[test]
+    fn checker_tells_downtime_on_recover() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let now = Utc::now();
+
+        assert!(matches!(
+            checker.advance(available(&[]), now),
+            StatusDifference::Recover { downtime: None, .. }
+        ));
+        checker.advance(unavailable(), now);
+
+        let restored = checker.state(now);
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        checker.restore(restored);
+
+        match checker.advance(available(&[]), now + chrono::Duration::minutes(10)) {
+            StatusDifference::Recover { downtime, .. } => {
+                assert_eq!(downtime, Some(chrono::Duration::minutes(10)))
+            }
+            _ => panic!("expected Recover"),
+        }
+    }
+
+    #[test]
+    fn checker_ignores_single_failure() {
+        let mut checker = setup_checker(3, 1, 0);
+        let now = Utc::now();
+
+        checker.advance(available(&["A"]), now);
+
+        assert!(matches!(
+            checker.advance(unavailable(), now),
+            StatusDifference::None { .. }
+        ));
+        assert!(matches!(
+            checker.advance(available(&["A", "B"]), now),
+            StatusDifference::PlayerChange { .. }
+        ));
+    }
+
+    #[test]
+    fn checker_announces_sustained_down() {
+        let mut checker = setup_checker(2, 2, 0);
+        let now = Utc::now();
+
+        checker.advance(available(&[]), now);
+        assert!(matches!(
+            checker.advance(available(&[]), now),
+            StatusDifference::Recover { .. }
+        ));
+
+        assert!(matches!(
+            checker.advance(unavailable(), now),
+            StatusDifference::None { .. }
+        ));
+        assert!(matches!(
+            checker.advance(unavailable(), now),
+            StatusDifference::Down { .. }
+        ));
+    }
+
+    #[test]
+    fn checker_waits_for_grace_period() {
+        let mut checker = setup_checker(1, 1, 120);
+        let start = Utc::now();
+
+        checker.advance(available(&[]), start);
+
+        assert!(matches!(
+            checker.advance(unavailable(), start),
+            StatusDifference::None { .. }
+        ));
+        assert!(matches!(
+            checker.advance(unavailable(), start + chrono::Duration::seconds(60)),
+            StatusDifference::None { .. }
+        ));
+        assert!(matches!(
+            checker.advance(unavailable(), start + chrono::Duration::seconds(120)),
+            StatusDifference::Down { .. }
+        ));
+    }
+
+    #[test]
+    fn checker_tracks_sessions() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let start = Utc::now();
+
+        checker.advance(available(&["A"]), start);
+        checker.advance(
+            available(&["A", "B"]),
+            start + chrono::Duration::minutes(10),
+        );
+
+        let difference = checker.advance(available(&["B"]), start + chrono::Duration::minutes(30));
+        match difference {
+            StatusDifference::PlayerChange {
+                finished_sessions, ..
+            } => {
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("A", "A"),
+                    chrono::Duration::minutes(30),
+                )]);
+                assert_eq!(finished_sessions, expected);
+            }
+            _ => panic!("expected PlayerChange"),
+        }
+    }
+
+    #[test]
+    fn checker_finishes_sessions_at_down_time() {
+        let mut checker = setup_checker(2, 1, 0);
+        let start = Utc::now();
+
+        checker.advance(available(&["A"]), start);
+        checker.advance(unavailable(), start + chrono::Duration::minutes(5));
+
+        let difference = checker.advance(unavailable(), start + chrono::Duration::minutes(6));
+        match difference {
+            StatusDifference::Down {
+                finished_sessions, ..
+            } => {
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("A", "A"),
+                    chrono::Duration::minutes(5),
+                )]);
+                assert_eq!(finished_sessions, expected);
+            }
+            _ => panic!("expected Down"),
+        }
+    }
+
+    #[test]
+    fn checker_counts_players_when_sample_is_truncated() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let now = Utc::now();
+
+        checker.advance(truncated(20, &["A", "B"]), now);
+
+        assert!(matches!(
+            checker.advance(truncated(20, &["C", "D"]), now),
+            StatusDifference::None { .. }
+        ));
+        assert!(matches!(
+            checker.advance(truncated(23, &["A", "D"]), now),
+            StatusDifference::PlayerCountChange {
+                joined_count: 3,
+                left_count: 0,
+                ..
+            }
+        ));
+        assert!(matches!(
+            checker.advance(available(&["A"]), now),
+            StatusDifference::PlayerCountChange {
+                joined_count: 0,
+                left_count: 22,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn checker_detects_server_info_changes() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let now = Utc::now();
+
+        let differences =
+            checker.check(with_server_info(ServerInfo::new("A", "1.18", 757, 20)), now);
+        assert_eq!(differences.len(), 1);
+
+        let differences =
+            checker.check(with_server_info(ServerInfo::new("B", "1.18", 757, 30)), now);
+        assert!(matches!(differences[0], StatusDifference::None { .. }));
+        assert!(matches!(
+            differences[1],
+            StatusDifference::MotdChange { .. }
+        ));
+        assert!(matches!(
+            differences[2],
+            StatusDifference::MaxPlayersChange {
+                old_max: 20,
+                max: 30
+            }
+        ));
+        assert_eq!(differences.len(), 3);
+    }
+
+    #[test]
+    fn checker_detects_version_change_across_restart() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let now = Utc::now();
+
+        checker.check(with_server_info(ServerInfo::new("A", "1.18", 757, 20)), now);
+        checker.check(unavailable(), now);
+
+        let differences =
+            checker.check(with_server_info(ServerInfo::new("A", "1.19", 759, 20)), now);
+        assert!(matches!(differences[0], StatusDifference::Recover { .. }));
+        assert!(matches!(
+            differences[1],
+            StatusDifference::VersionChange {
+                old_protocol: 757,
+                protocol: 759,
+                ..
+            }
+        ));
+        assert_eq!(differences.len(), 2);
+    }
+
+    #[test]
+    fn checker_restores_state() {
+        let start = Utc::now();
+
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        checker.check(available(&["A"]), start);
+        let json = serde_json::to_string(&checker.state(start)).unwrap();
+
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        checker.restore(serde_json::from_str(&json).unwrap());
+
+        let differences = checker.check(available(&["A"]), start + chrono::Duration::minutes(1));
+        assert!(matches!(differences[0], StatusDifference::None { .. }));
+
+        let difference = checker.advance(available(&[]), start + chrono::Duration::minutes(10));
+        match difference {
+            StatusDifference::PlayerChange {
+                finished_sessions, ..
+            } => {
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("A", "A"),
+                    chrono::Duration::minutes(10),
+                )]);
+                assert_eq!(finished_sessions, expected);
+            }
+            _ => panic!("expected PlayerChange"),
+        }
+    }
+
+    #[test]
+    fn checker_detects_count_events() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_count_rules(CountRules {
+                thresholds: vec![2],
+                full: false,
+                empty: true,
+                hysteresis: 0,
+            });
+        let now = Utc::now();
+
+        assert_eq!(checker.check(available(&["A"]), now).len(), 1);
+
+        let differences = checker.check(available(&["A", "B"]), now);
+        assert!(matches!(
+            differences[..],
+            [
+                StatusDifference::PlayerChange { .. },
+                StatusDifference::PlayerCountReached { threshold: 2, .. }
+            ]
+        ));
+
+        let differences = checker.check(available(&[]), now);
+        assert!(matches!(
+            differences[..],
+            [
+                StatusDifference::PlayerChange { .. },
+                StatusDifference::ServerEmpty
+            ]
+        ));
+    }
+
+    #[test]
+    fn checker_detects_new_players() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_registry(PlayerRegistry::default());
+        let now = Utc::now();
+
+        assert_eq!(checker.check(available(&["A"]), now).len(), 1);
+        assert_eq!(checker.check(available(&[]), now).len(), 1);
+
+        let differences = checker.check(available(&["A", "B"]), now);
+        match differences[..] {
+            [StatusDifference::PlayerChange { .. }, StatusDifference::NewPlayer { ref players }] => {
+                assert_eq!(format!("{}", players), "B");
+            }
+            _ => panic!("expected PlayerChange and NewPlayer"),
+        }
+    }
+
+    #[test]
+    fn checker_counts_players_out_after_grace() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_rejoin_grace(chrono::Duration::seconds(60))
+            .with_count_rules(CountRules {
+                thresholds: vec![2],
+                full: false,
+                empty: true,
+                hysteresis: 0,
+            });
+        let start = Utc::now();
+        let at = |seconds| start + chrono::Duration::seconds(seconds);
+
+        checker.check(available(&["A", "B"]), at(0));
+
+        // the last players leave, and the server is empty once the grace period is over
+        let differences = checker.check(available(&[]), at(10));
+        assert!(matches!(differences[..], [StatusDifference::None { .. }]));
+        let differences = checker.check(available(&[]), at(70));
+        assert!(matches!(
+            differences[..],
+            [
+                StatusDifference::None { .. },
+                StatusDifference::PlayerChange { .. },
+                StatusDifference::ServerEmpty
+            ]
+        ));
+
+        // the threshold is re-armed
+        let differences = checker.check(available(&["A", "B"]), at(80));
+        assert!(matches!(
+            differences[..],
+            [
+                StatusDifference::PlayerChange { .. },
+                StatusDifference::PlayerCountReached { threshold: 2, .. }
+            ]
+        ));
+    }
+
+    #[test]
+    fn checker_suppresses_rejoins_within_grace() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_rejoin_grace(chrono::Duration::seconds(60));
+        let start = Utc::now();
+        let at = |seconds| start + chrono::Duration::seconds(seconds);
+
+        checker.check(available(&["A", "B"]), at(0));
+
+        // B drops and comes back within the grace period
+        let differences = checker.check(available(&["A"]), at(10));
+        assert!(matches!(differences[..], [StatusDifference::None { .. }]));
+        let differences = checker.check(available(&["A", "B", "C"]), at(30));
+        match differences[..] {
+            [StatusDifference::PlayerChange {
+                ref joined_players,
+                ref left_players,
+                ..
+            }] => {
+                assert_eq!(joined_players.to_string(), "C");
+                assert!(left_players.is_empty());
+            }
+            _ => panic!("expected PlayerChange"),
+        }
+
+        // B leaves for good, and the session lasts until B left
+        checker.check(available(&["A", "C"]), at(100));
+        let restored = checker.state(at(100));
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_rejoin_grace(chrono::Duration::seconds(60));
+        checker.restore(restored);
+
+        assert_eq!(checker.check(available(&["A", "C"]), at(159)).len(), 1);
+        let differences = checker.check(available(&["A", "C"]), at(160));
+        match differences[..] {
+            [StatusDifference::None { .. }, StatusDifference::PlayerChange {
+                ref joined_players,
+                ref left_players,
+                ref finished_sessions,
+                online_count,
+                ..
+            }] => {
+                assert!(joined_players.is_empty());
+                assert_eq!(left_players.to_string(), "B");
+                assert_eq!(online_count, 2);
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("B", "B"),
+                    chrono::Duration::seconds(100),
+                )]);
+                assert_eq!(*finished_sessions, expected);
+            }
+            _ => panic!("expected PlayerChange of B"),
+        }
+    }
+
+    #[test]
+    fn checker_expires_rejoins_across_truncated_samples() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default())
+            .with_rejoin_grace(chrono::Duration::seconds(60));
+        let start = Utc::now();
+        let at = |seconds| start + chrono::Duration::seconds(seconds);
+
+        checker.check(available(&["A", "B"]), at(0));
+        checker.check(available(&["A"]), at(10));
+        // the sample is truncated while B is within the grace period
+        checker.check(truncated(5, &["C", "D"]), at(20));
+
+        let differences = checker.check(truncated(5, &["C", "D"]), at(70));
+        match differences[..] {
+            [StatusDifference::None { .. }, StatusDifference::PlayerChange {
+                ref left_players,
+                ref finished_sessions,
+                ..
+            }] => {
+                assert_eq!(left_players.to_string(), "B");
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("B", "B"),
+                    chrono::Duration::seconds(10),
+                )]);
+                assert_eq!(*finished_sessions, expected);
+            }
+            _ => panic!("expected PlayerChange of B"),
+        }
+    }
+
+    #[test]
+    fn checker_ignores_sessions_of_truncated_samples() {
+        let mut checker = StatusChecker::new("localhost", 25565, Thresholds::default());
+        let start = Utc::now();
+        let at = |minutes| start + chrono::Duration::minutes(minutes);
+
+        checker.advance(available(&["A"]), at(0));
+        checker.advance(truncated(5, &["B", "C"]), at(10));
+        checker.advance(truncated(6, &["D", "B"]), at(20));
+        checker.advance(truncated(6, &["C", "A"]), at(25));
+        // the complete list is back, and B's session is counted from here
+        checker.advance(available(&["B"]), at(30));
+
+        match checker.advance(available(&[]), at(45)) {
+            StatusDifference::PlayerChange {
+                finished_sessions, ..
+            } => {
+                let expected = Sessions::from(vec![Session::new(
+                    Player::new("B", "B"),
+                    chrono::Duration::minutes(15),
+                )]);
+                assert_eq!(finished_sessions, expected);
+            }
+            _ => panic!("expected PlayerChange"),
         }
     }
 }
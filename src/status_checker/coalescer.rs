@@ -0,0 +1,289 @@
+use super::StatusDifference;
+use crate::models::{Players, Session, Sessions};
+use chrono::{DateTime, Duration, Utc};
+
+/// merges the `PlayerChange` differences within a window into one,
+/// so players who join one after another are announced together.
+pub struct Coalescer {
+    window: Duration,
+    /// the merged difference and when the window opened
+    pending: Option<(DateTime<Utc>, StatusDifference)>,
+    /// the differences caused by the pending one, which are sent after it
+    held: Vec<StatusDifference>,
+    /// the first-time players whose welcome was dropped
+    unwelcomed: Players,
+}
+
+impl Coalescer {
+    /// a window of zero disables coalescing.
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: None,
+            held: Vec::new(),
+            unwelcomed: Players::default(),
+        }
+    }
+
+    /// returns the differences to be notified right now.
+    /// `PlayerChange` is held until the window closes, with the differences which follow it.
+    /// the server going down or recovering, or a change of the count only, closes the window
+    /// first, so the messages keep their order.
+    pub fn push(
+        &mut self,
+        difference: StatusDifference,
+        now: DateTime<Utc>,
+    ) -> Vec<StatusDifference> {
+        if self.window <= Duration::zero() {
+            return vec![difference];
+        }
+
+        match difference {
+            StatusDifference::PlayerChange { .. } => {
+                self.pending = match self.pending.take() {
+                    Some((since, pending)) => Some((since, merge(pending, difference))),
+                    None => Some((now, difference)),
+                };
+                Vec::new()
+            }
+            StatusDifference::Down { .. }
+            | StatusDifference::Recover { .. }
+            | StatusDifference::PlayerCountChange { .. } => {
+                let mut differences = self.flush();
+                differences.push(difference);
+                differences
+            }
+            StatusDifference::NewPlayer { .. }
+            | StatusDifference::ServerEmpty
+            | StatusDifference::PlayerCountReached { .. }
+            | StatusDifference::ServerFull { .. }
+                if self.pending.is_some() =>
+            {
+                self.held.push(difference);
+                Vec::new()
+            }
+            _ => vec![difference],
+        }
+    }
+
+    /// returns the merged difference and the ones held with it once the window has closed.
+    pub fn poll(&mut self, now: DateTime<Utc>) -> Vec<StatusDifference> {
+        match self.pending {
+            Some((since, _)) if now - since >= self.window => self.flush(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// returns the merged difference and the ones held with it regardless of the window,
+    /// e.g. before stopping. if the changes cancelled each other out, the players who joined
+    /// and the server getting empty aren't told either. see `take_unwelcomed`.
+    pub fn flush(&mut self) -> Vec<StatusDifference> {
+        let held = std::mem::take(&mut self.held);
+        let difference = match self.pending.take() {
+            Some((_, difference)) => difference,
+            None => return held,
+        };
+
+        match difference {
+            StatusDifference::PlayerChange {
+                ref joined_players,
+                ref left_players,
+                ..
+            } if joined_players.is_empty() && left_players.is_empty() => {
+                let mut differences = Vec::new();
+                for difference in held {
+                    match difference {
+                        StatusDifference::NewPlayer { ref players } => {
+                            self.unwelcomed = &self.unwelcomed | players;
+                        }
+                        StatusDifference::ServerEmpty => {}
+                        difference => differences.push(difference),
+                    }
+                }
+                differences
+            }
+            _ => std::iter::once(difference).chain(held).collect(),
+        }
+    }
+
+    /// returns the first-time players whose welcome was dropped because they left within
+    /// the window. they have to be forgotten by the registry to be welcomed on their next visit.
+    pub fn take_unwelcomed(&mut self) -> Players {
+        std::mem::take(&mut self.unwelcomed)
+    }
+
+    /// how long until the window closes, if a difference is held.
+    pub fn remaining(&self, now: DateTime<Utc>) -> Option<Duration> {
+        self.pending
+            .as_ref()
+            .map(|(since, _)| (*since + self.window - now).max(Duration::zero()))
+    }
+}
+
+/// a player who joined and left (or left and came back) within the window cancels out.
+/// the players online are the latest ones.
+fn merge(earlier: StatusDifference, later: StatusDifference) -> StatusDifference {
+    match (earlier, later) {
+        (
+            StatusDifference::PlayerChange {
+                joined_players: earlier_joined,
+                left_players: earlier_left,
+                finished_sessions: earlier_sessions,
+                ..
+            },
+            StatusDifference::PlayerChange {
+                online_count,
+                current_players,
+                joined_players: later_joined,
+                left_players: later_left,
+                finished_sessions: later_sessions,
+            },
+        ) => {
+            let joined_players =
+                &(&earlier_joined - &later_left) | &(&later_joined - &earlier_left);
+            let left_players = &(&earlier_left - &later_joined) | &(&later_left - &earlier_joined);
+
+            // the latest session of each player who is still gone
+            let mut sessions: Vec<Session> = Vec::new();
+            for session in later_sessions.iter().chain(earlier_sessions.iter()) {
+                let player = session.player();
+                if is_in(&left_players, player.id())
+                    && !sessions.iter().any(|s| s.player().id() == player.id())
+                {
+                    sessions.push(session.clone());
+                }
+            }
+
+            StatusDifference::PlayerChange {
+                online_count,
+                current_players,
+                joined_players,
+                left_players,
+                finished_sessions: Sessions::from(sessions),
+            }
+        }
+        (_, later) => later,
+    }
+}
+
+fn is_in(players: &Players, id: &str) -> bool {
+    players.iter().any(|player| player.id() == id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Player;
+    use chrono::TimeZone;
+
+    fn player_change(current: &[&str], joined: &[&str], left: &[&str]) -> StatusDifference {
+        let players =
+            |names: &[&str]| Players::from_iter(names.iter().map(|name| Player::new(name, name)));
+        let sessions: Vec<Session> = left
+            .iter()
+            .map(|name| Session::new(Player::new(name, name), Duration::minutes(5)))
+            .collect();
+
+        StatusDifference::PlayerChange {
+            online_count: current.len() as u32,
+            current_players: players(current),
+            joined_players: players(joined),
+            left_players: players(left),
+            finished_sessions: Sessions::from(sessions),
+        }
+    }
+
+    #[test]
+    fn coalescer_merges_player_changes() {
+        let now = Utc.ymd(2022, 6, 4).and_hms(12, 0, 0);
+        let mut coalescer = Coalescer::new(Duration::seconds(30));
+
+        assert!(coalescer
+            .push(player_change(&["A", "X"], &["A"], &["Y"]), now)
+            .is_empty());
+        assert!(coalescer
+            .push(
+                player_change(&["A", "B", "C"], &["B", "C"], &["X"]),
+                now + Duration::seconds(10)
+            )
+            .is_empty());
+        // C left within the window, and Y came back
+        assert!(coalescer
+            .push(
+                player_change(&["A", "B", "Y"], &["Y"], &["C"]),
+                now + Duration::seconds(20)
+            )
+            .is_empty());
+        assert_eq!(
+            coalescer.remaining(now + Duration::seconds(20)),
+            Some(Duration::seconds(10))
+        );
+
+        assert!(coalescer.poll(now + Duration::seconds(29)).is_empty());
+        let merged = coalescer
+            .poll(now + Duration::seconds(30))
+            .into_iter()
+            .next()
+            .unwrap();
+        match merged {
+            StatusDifference::PlayerChange {
+                online_count,
+                joined_players,
+                left_players,
+                finished_sessions,
+                ..
+            } => {
+                assert_eq!(online_count, 3);
+                assert_eq!(joined_players.to_string(), "A, B");
+                assert_eq!(left_players.to_string(), "X");
+                assert_eq!(finished_sessions.to_string(), "X (5m)");
+            }
+            _ => panic!("unexpected difference: {}", merged.kind()),
+        }
+        assert_eq!(coalescer.remaining(now + Duration::seconds(30)), None);
+    }
+
+    #[test]
+    fn coalescer_cancels_out_and_keeps_order() {
+        let now = Utc.ymd(2022, 6, 4).and_hms(12, 0, 0);
+        let mut coalescer = Coalescer::new(Duration::seconds(30));
+
+        let new_player = || StatusDifference::NewPlayer {
+            players: Players::from_iter(vec![Player::new("A", "A")]),
+        };
+
+        // the events caused by the changes are held with them, and dropped if they cancel out
+        coalescer.push(player_change(&["A"], &["A"], &[]), now);
+        assert!(coalescer.push(new_player(), now).is_empty());
+        coalescer.push(player_change(&[], &[], &["A"]), now);
+        assert!(coalescer
+            .push(StatusDifference::ServerEmpty, now)
+            .is_empty());
+        assert!(coalescer.flush().is_empty());
+        assert_eq!(coalescer.take_unwelcomed().to_string(), "A");
+        assert!(coalescer.take_unwelcomed().is_empty());
+
+        coalescer.push(player_change(&["A"], &["A"], &[]), now);
+        coalescer.push(new_player(), now);
+        let differences = coalescer.poll(now + Duration::seconds(30));
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].kind(), "player_change");
+        assert_eq!(differences[1].kind(), "new_player");
+
+        coalescer.push(player_change(&["A"], &["A"], &[]), now);
+        let down = StatusDifference::Down {
+            reason: "timeout".to_owned(),
+            finished_sessions: Sessions::default(),
+        };
+        let differences = coalescer.push(down, now);
+        assert_eq!(differences.len(), 2);
+        assert_eq!(differences[0].kind(), "player_change");
+        assert_eq!(differences[1].kind(), "down");
+
+        let mut disabled = Coalescer::new(Duration::zero());
+        assert_eq!(
+            disabled.push(player_change(&["A"], &["A"], &[]), now).len(),
+            1
+        );
+    }
+}
@@ -0,0 +1,163 @@
+use super::StatusDifference;
+
+/// levels of the number of online players which should be notified.
+#[derive(Clone, Debug, Default)]
+pub struct CountRules {
+    /// notify when the number of online players reaches one of them.
+    pub thresholds: Vec<u32>,
+    /// notify when the server becomes full.
+    pub full: bool,
+    /// notify when all players left the server.
+    pub empty: bool,
+    /// the number of players by which the count has to move back before the same event fires again.
+    pub hysteresis: u32,
+}
+
+/// fires once when a condition is reached, and re-arms only after the condition is cleared enough.
+#[derive(Default)]
+struct Trigger {
+    /// `None` until the first observation, in order not to fire on start.
+    armed: Option<bool>,
+}
+
+impl Trigger {
+    fn update(&mut self, reached: bool, rearmed: bool) -> bool {
+        match self.armed {
+            None => {
+                self.armed = Some(!reached);
+                false
+            }
+            Some(true) if reached => {
+                self.armed = Some(false);
+                true
+            }
+            Some(false) if rearmed => {
+                self.armed = Some(true);
+                false
+            }
+            _ => false,
+        }
+    }
+}
+
+pub struct CountTriggers {
+    rules: CountRules,
+    thresholds: Vec<Trigger>,
+    full: Trigger,
+    empty: Trigger,
+}
+
+impl CountTriggers {
+    pub fn new(rules: CountRules) -> Self {
+        let thresholds = rules
+            .thresholds
+            .iter()
+            .map(|_| Trigger::default())
+            .collect();
+
+        Self {
+            rules,
+            thresholds,
+            full: Trigger::default(),
+            empty: Trigger::default(),
+        }
+    }
+
+    /// forgets the previous observations, e.g. when the server went down.
+    pub fn reset(&mut self) {
+        *self = Self::new(self.rules.clone());
+    }
+
+    pub fn update(&mut self, online_count: u32, max: u32) -> Vec<StatusDifference> {
+        let hysteresis = self.rules.hysteresis;
+        let mut differences = Vec::new();
+
+        for (threshold, trigger) in self.rules.thresholds.iter().zip(self.thresholds.iter_mut()) {
+            let reached = online_count >= *threshold;
+            let rearmed = online_count + hysteresis < *threshold;
+            if trigger.update(reached, rearmed) {
+                differences.push(StatusDifference::PlayerCountReached {
+                    threshold: *threshold,
+                    online_count,
+                    max,
+                });
+            }
+        }
+
+        if self.rules.full && max > 0 {
+            let reached = online_count >= max;
+            let rearmed = online_count + hysteresis < max;
+            if self.full.update(reached, rearmed) {
+                differences.push(StatusDifference::ServerFull { online_count, max });
+            }
+        }
+
+        if self.rules.empty {
+            let reached = online_count == 0;
+            let rearmed = online_count > hysteresis;
+            if self.empty.update(reached, rearmed) {
+                differences.push(StatusDifference::ServerEmpty);
+            }
+        }
+
+        differences
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_triggers() -> CountTriggers {
+        CountTriggers::new(CountRules {
+            thresholds: vec![5],
+            full: true,
+            empty: true,
+            hysteresis: 1,
+        })
+    }
+
+    #[test]
+    fn count_triggers_do_not_fire_on_start() {
+        let mut triggers = setup_triggers();
+        assert!(triggers.update(0, 10).is_empty());
+        assert!(triggers.update(1, 10).is_empty());
+    }
+
+    #[test]
+    fn count_triggers_fire_once_with_hysteresis() {
+        let mut triggers = setup_triggers();
+        triggers.update(3, 10);
+
+        let differences = triggers.update(5, 10);
+        assert!(matches!(
+            differences[..],
+            [StatusDifference::PlayerCountReached { threshold: 5, .. }]
+        ));
+
+        // 4 is within the hysteresis, so reaching 5 again doesn't fire
+        assert!(triggers.update(4, 10).is_empty());
+        assert!(triggers.update(5, 10).is_empty());
+
+        assert!(triggers.update(3, 10).is_empty());
+        assert_eq!(triggers.update(5, 10).len(), 1);
+    }
+
+    #[test]
+    fn count_triggers_full_and_empty() {
+        let mut triggers = setup_triggers();
+        triggers.update(3, 4);
+
+        assert!(matches!(
+            triggers.update(4, 4)[..],
+            [StatusDifference::ServerFull {
+                online_count: 4,
+                max: 4
+            }]
+        ));
+        assert!(matches!(
+            triggers.update(0, 4)[..],
+            [StatusDifference::ServerEmpty]
+        ));
+    }
+}
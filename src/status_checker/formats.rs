@@ -1,56 +1,318 @@
 extern crate chrono;
-extern crate strfmt;
+extern crate tera;
 
-use self::strfmt::Format;
-use crate::models::Players;
+use crate::digest::Digest;
+use crate::locale::{DurationStyle, Locale};
+use crate::models::{Players, Sessions};
 use crate::status_checker::StatusDifference;
-use std::collections::HashMap;
+use crate::template::{Error as TemplateError, PlayerContext, Templates};
+use crate::timezone::Timezone;
+use chrono::{DateTime, Duration, Utc};
 use std::convert;
+use tera::Context;
 
 #[derive(Debug)]
 pub enum Error {
     FormatError(String),
 }
 
-impl convert::From<strfmt::FmtError> for Error {
-    fn from(from: strfmt::FmtError) -> Error {
-        Error::FormatError(from.to_string())
+impl convert::From<TemplateError> for Error {
+    fn from(from: TemplateError) -> Error {
+        match from {
+            TemplateError::InvalidTemplate(name, reason) => {
+                Error::FormatError(format!("invalid {}: {}", name, reason))
+            }
+            TemplateError::RenderError(name, reason) => {
+                Error::FormatError(format!("couldn't render {}: {}", name, reason))
+            }
+        }
     }
 }
 
-pub struct StatusFormats {
+/// the sources of the message formats, in the template syntax or in the legacy `{name}` format.
+/// an empty format disables the message.
+pub struct FormatStrings {
     pub recover_msg: String,
     pub down_msg: String,
     pub join_fmt: String,
     pub leave_fmt: String,
+    pub join_count_fmt: String,
+    pub leave_count_fmt: String,
+    pub motd_change_fmt: String,
+    pub version_change_fmt: String,
+    pub max_players_change_fmt: String,
+    pub count_reached_fmt: String,
+    pub full_fmt: String,
+    pub empty_msg: String,
+    pub new_player_fmt: String,
+    pub digest_fmt: String,
+    pub stopped_msg: String,
     pub players_fmt: String,
     pub time_fmt: String,
+    /// the language of the plural forms, durations and the names in `time_fmt`.
+    pub locale: Locale,
+    /// the timezone of `time_fmt` and the peak time in digests.
+    pub timezone: Timezone,
+    pub duration_style: DurationStyle,
+}
+
+/// the variables about the server which every format can use.
+#[derive(Serialize, Clone, Debug, Default, PartialEq)]
+pub struct ServerContext {
+    pub hostname: String,
+    pub port: u16,
+    /// the last known maximum number of players, version and MOTD, which are kept while the server is down
+    pub max: Option<u32>,
+    pub version: Option<String>,
+    pub motd: Option<String>,
+    /// (ms) time taken by the latest check, unknown while the server is down
+    pub latency: Option<u64>,
+}
+
+/// the variables which every format can use, in addition to the ones of each format.
+const COMMON_VARIABLES: &[&str] = &[
+    "event", "reason", "hostname", "port", "max", "version", "motd", "latency", "downtime", "time",
+];
+
+pub struct StatusFormats {
+    templates: Templates,
+    time_fmt: String,
+    /// the formats which place the time by `{time}`.
+    /// the messages made of them don't get the time at the top.
+    time_formats: Vec<&'static str>,
+    locale: Locale,
+    timezone: Timezone,
+    duration_style: DurationStyle,
 }
 
 impl StatusFormats {
-    pub fn format(&self, status_difference: &StatusDifference) -> Result<Option<String>, Error> {
+    /// parses every format, so broken formats and unknown placeholders are found
+    /// before anything is sent.
+    pub fn new(strings: &FormatStrings) -> Result<Self, Error> {
+        let mut templates = Templates::new(strings.locale);
+        let mut time_formats = Vec::new();
+        for (name, source, variables) in Self::sources(strings) {
+            if Self::add(&mut templates, name, source, variables)? {
+                time_formats.push(name);
+            }
+        }
+
+        Ok(Self {
+            templates,
+            time_fmt: strings.time_fmt.clone(),
+            time_formats,
+            locale: strings.locale,
+            timezone: strings.timezone,
+            duration_style: strings.duration_style,
+        })
+    }
+
+    /// finds the problems in every format, not only the first one as `new` does.
+    pub fn check(strings: &FormatStrings) -> Vec<(&'static str, Error)> {
+        let mut templates = Templates::new(strings.locale);
+
+        Self::sources(strings)
+            .into_iter()
+            .filter_map(|(name, source, variables)| {
+                Self::add(&mut templates, name, source, variables)
+                    .err()
+                    .map(|e| (name, e))
+            })
+            .collect()
+    }
+
+    /// the formats and the variables which each of them can use besides `COMMON_VARIABLES`.
+    fn sources(strings: &FormatStrings) -> Vec<(&'static str, &String, &'static [&'static str])> {
+        let players: &[&str] = &["players", "count"];
+        vec![
+            ("recover_msg", &strings.recover_msg, &[][..]),
+            ("down_msg", &strings.down_msg, &[]),
+            ("join_fmt", &strings.join_fmt, players),
+            ("leave_fmt", &strings.leave_fmt, players),
+            ("join_count_fmt", &strings.join_count_fmt, &["count"]),
+            ("leave_count_fmt", &strings.leave_count_fmt, &["count"]),
+            ("motd_change_fmt", &strings.motd_change_fmt, &["old_motd"]),
+            (
+                "version_change_fmt",
+                &strings.version_change_fmt,
+                &["old_version", "old_protocol", "protocol"],
+            ),
+            (
+                "max_players_change_fmt",
+                &strings.max_players_change_fmt,
+                &["old_max"],
+            ),
+            (
+                "count_reached_fmt",
+                &strings.count_reached_fmt,
+                &["threshold", "count"],
+            ),
+            ("full_fmt", &strings.full_fmt, &["count"]),
+            ("empty_msg", &strings.empty_msg, &[]),
+            ("new_player_fmt", &strings.new_player_fmt, players),
+            (
+                "digest_fmt",
+                &strings.digest_fmt,
+                &[
+                    "players",
+                    "unique_count",
+                    "peak_count",
+                    "peak_time",
+                    "playtime",
+                    "outages",
+                ],
+            ),
+            ("stopped_msg", &strings.stopped_msg, &[]),
+            ("players_fmt", &strings.players_fmt, players),
+        ]
+    }
+
+    /// adds the format to `templates`, checking its placeholders.
+    /// returns true if the format places the time by `{time}`.
+    fn add(
+        templates: &mut Templates,
+        name: &str,
+        source: &str,
+        variables: &[&str],
+    ) -> Result<bool, Error> {
+        if source.is_empty() {
+            return Ok(false);
+        }
+
+        templates.add(name, source)?;
+
+        let used = templates.variables(name);
+        let unknown: Vec<&str> = used
+            .iter()
+            .map(String::as_str)
+            .filter(|v| !COMMON_VARIABLES.contains(v) && !variables.contains(v))
+            .collect();
+        if !unknown.is_empty() {
+            return Err(Error::FormatError(format!(
+                "unknown placeholder in {}: {}",
+                name,
+                unknown.join(", ")
+            )));
+        }
+
+        Ok(used.iter().any(|v| v == "time"))
+    }
+
+    pub fn format(
+        &self,
+        status_difference: &StatusDifference,
+        server: &ServerContext,
+    ) -> Result<Option<String>, Error> {
         use crate::status_checker::StatusDifference::*;
 
         let mut buffer = String::with_capacity(560); // 140 chars * 4 bytes
 
-        self.format_time(&mut buffer);
+        self.format_time(&mut buffer, &Self::names_of(status_difference));
+
+        let common = self.context(status_difference, server);
 
         // join / leave
         match *status_difference {
             PlayerChange {
                 ref joined_players,
                 ref left_players,
+                ref finished_sessions,
+                ..
+            } => {
+                self.format_join(&mut buffer, &common, joined_players)?;
+                self.format_leave(&mut buffer, &common, left_players, finished_sessions)?;
+            }
+            PlayerCountChange {
+                joined_count,
+                left_count,
                 ..
             } => {
-                self.format_join(&mut buffer, joined_players)?;
-                self.format_leave(&mut buffer, left_players)?;
+                self.format_count(&mut buffer, &common, "join_count_fmt", joined_count)?;
+                self.format_count(&mut buffer, &common, "leave_count_fmt", left_count)?;
             }
             Recover { .. } => {
-                buffer.push_str(&self.recover_msg);
+                if let Some(message) = self.render("recover_msg", &common)? {
+                    buffer.push_str(&message);
+                }
                 buffer.push('\n');
             }
             Down { .. } => {
-                buffer.push_str(&self.down_msg);
+                if let Some(message) = self.render("down_msg", &common)? {
+                    buffer.push_str(&message);
+                }
+            }
+            MotdChange {
+                ref old_motd,
+                ref motd,
+            } => {
+                let mut context = common.clone();
+                context.insert("old_motd", old_motd);
+                context.insert("motd", motd);
+
+                if !self.build_change(&mut buffer, "motd_change_fmt", &context)? {
+                    return Ok(Option::None);
+                }
+            }
+            VersionChange {
+                ref old_version,
+                ref version,
+                old_protocol,
+                protocol,
+            } => {
+                let mut context = common.clone();
+                context.insert("old_version", old_version);
+                context.insert("version", version);
+                context.insert("old_protocol", &old_protocol);
+                context.insert("protocol", &protocol);
+
+                if !self.build_change(&mut buffer, "version_change_fmt", &context)? {
+                    return Ok(Option::None);
+                }
+            }
+            MaxPlayersChange { old_max, max } => {
+                let mut context = common.clone();
+                context.insert("old_max", &old_max);
+                context.insert("max", &max);
+
+                if !self.build_change(&mut buffer, "max_players_change_fmt", &context)? {
+                    return Ok(Option::None);
+                }
+            }
+            PlayerCountReached {
+                threshold,
+                online_count,
+                max,
+            } => {
+                let mut context = common.clone();
+                context.insert("threshold", &threshold);
+                context.insert("count", &online_count);
+                context.insert("max", &max);
+
+                if !self.build_change(&mut buffer, "count_reached_fmt", &context)? {
+                    return Ok(Option::None);
+                }
+            }
+            ServerFull { online_count, max } => {
+                let mut context = common.clone();
+                context.insert("count", &online_count);
+                context.insert("max", &max);
+
+                if !self.build_change(&mut buffer, "full_fmt", &context)? {
+                    return Ok(Option::None);
+                }
+            }
+            ServerEmpty => {
+                if !self.build_change(&mut buffer, "empty_msg", &common)? {
+                    return Ok(Option::None);
+                }
+            }
+            NewPlayer { ref players } => {
+                let mut context = common.clone();
+                Self::insert_players(&mut context, PlayerContext::from_players(players));
+
+                if !self.build_change(&mut buffer, "new_player_fmt", &context)? {
+                    return Ok(Option::None);
+                }
             }
             None { .. } => {
                 return Ok(Option::None);
@@ -65,12 +327,17 @@ impl StatusFormats {
                 ref current_players,
                 ..
             }
+            | &PlayerCountChange {
+                online_count,
+                ref current_players,
+                ..
+            }
             | &Recover {
                 online_count,
                 ref current_players,
                 ..
             } => {
-                self.format_current_players(&mut buffer, online_count, current_players)?;
+                self.format_current_players(&mut buffer, &common, online_count, current_players)?;
             }
             _ => {}
         };
@@ -78,55 +345,241 @@ impl StatusFormats {
         Ok(Some(buffer))
     }
 
-    fn format_time(&self, buffer: &mut String) {
-        if !self.time_fmt.is_empty() {
-            let current_time = chrono::Local::now();
-            let formatted_time = current_time.format(&self.time_fmt);
-            buffer.push_str(&formatted_time.to_string());
+    pub fn format_digest(&self, digest: &Digest, server: &ServerContext) -> Result<String, Error> {
+        let mut buffer = String::with_capacity(560);
+
+        self.format_time(&mut buffer, &["digest_fmt"]);
+
+        let (peak_count, peak_time) = match digest.peak {
+            Some((count, at)) => (count, self.time_at(at, "%H:%M")),
+            Option::None => (0, "-".to_owned()),
+        };
+
+        let mut context = self.common_context("digest", Option::None, Option::None, server);
+        context.insert("unique_count", &digest.players.iter().count());
+        context.insert("peak_count", &peak_count);
+        context.insert("peak_time", &peak_time);
+        context.insert("playtime", &self.format_duration(&digest.playtime));
+        context.insert("outages", &digest.outages);
+        context.insert("players", &PlayerContext::from_players(&digest.players));
+
+        if let Some(message) = self.render("digest_fmt", &context)? {
+            buffer.push_str(&message);
+        }
+
+        Ok(buffer)
+    }
+
+    /// returns `None` if `stopped_msg` is empty.
+    pub fn format_stopped(&self, server: &ServerContext) -> Result<Option<String>, Error> {
+        let context = self.common_context("stopped", Option::None, Option::None, server);
+        self.render("stopped_msg", &context)
+    }
+
+    fn context(&self, status_difference: &StatusDifference, server: &ServerContext) -> Context {
+        let reason = match *status_difference {
+            StatusDifference::Down { ref reason, .. } => Some(reason.as_str()),
+            _ => Option::None,
+        };
+
+        let downtime = match *status_difference {
+            StatusDifference::Recover {
+                downtime: Some(ref downtime),
+                ..
+            } => Some(self.format_duration(downtime)),
+            _ => Option::None,
+        };
+
+        self.common_context(status_difference.kind(), reason, downtime, server)
+    }
+
+    /// the variables which every format can use. see `COMMON_VARIABLES`.
+    /// the ones which don't apply to the event are null, which is rendered as an empty string.
+    fn common_context(
+        &self,
+        event: &str,
+        reason: Option<&str>,
+        downtime: Option<String>,
+        server: &ServerContext,
+    ) -> Context {
+        let mut context = Context::from_serialize(server).unwrap_or_default();
+        context.insert("event", event);
+        context.insert("reason", &reason);
+        context.insert("downtime", &downtime);
+        context.insert("time", &self.time_at(Utc::now(), &self.time_fmt));
+        context
+    }
+
+    /// the time in the timezone, with the names in the language.
+    fn time_at(&self, at: DateTime<Utc>, fmt: &str) -> String {
+        match self.timezone {
+            Timezone::Local => self
+                .locale
+                .format_time(&at.with_timezone(&chrono::Local), fmt),
+            Timezone::Named(tz) => self.locale.format_time(&at.with_timezone(&tz), fmt),
+        }
+    }
+
+    fn format_duration(&self, duration: &Duration) -> String {
+        self.locale.format_duration(duration, self.duration_style)
+    }
+
+    /// `players` and the number of them as `count`.
+    fn insert_players(context: &mut Context, players: Vec<PlayerContext>) {
+        context.insert("count", &players.len());
+        context.insert("players", &players);
+    }
+
+    /// returns `None` if the format is empty.
+    fn render(&self, name: &str, context: &Context) -> Result<Option<String>, Error> {
+        if !self.templates.contains(name) {
+            return Ok(Option::None);
+        }
+        Ok(Some(self.templates.render(name, context)?))
+    }
+
+    /// the formats which make the message of the difference.
+    fn names_of(status_difference: &StatusDifference) -> Vec<&'static str> {
+        use crate::status_checker::StatusDifference::*;
+
+        let mut names = Vec::new();
+        match *status_difference {
+            PlayerChange {
+                ref joined_players,
+                ref left_players,
+                ..
+            } => {
+                if !joined_players.is_empty() {
+                    names.push("join_fmt");
+                }
+                if !left_players.is_empty() {
+                    names.push("leave_fmt");
+                }
+                names.push("players_fmt");
+            }
+            PlayerCountChange {
+                joined_count,
+                left_count,
+                ..
+            } => {
+                if joined_count > 0 {
+                    names.push("join_count_fmt");
+                }
+                if left_count > 0 {
+                    names.push("leave_count_fmt");
+                }
+                names.push("players_fmt");
+            }
+            Recover { .. } => names.extend(["recover_msg", "players_fmt"]),
+            Down { .. } => names.push("down_msg"),
+            MotdChange { .. } => names.push("motd_change_fmt"),
+            VersionChange { .. } => names.push("version_change_fmt"),
+            MaxPlayersChange { .. } => names.push("max_players_change_fmt"),
+            PlayerCountReached { .. } => names.push("count_reached_fmt"),
+            ServerFull { .. } => names.push("full_fmt"),
+            ServerEmpty => names.push("empty_msg"),
+            NewPlayer { .. } => names.push("new_player_fmt"),
+            None { .. } => {}
+        }
+        names
+    }
+
+    /// puts the time at the top, unless one of the formats of the message places it.
+    fn format_time(&self, buffer: &mut String, names: &[&str]) {
+        let placed = names.iter().any(|name| self.time_formats.contains(name));
+        if !placed && !self.time_fmt.is_empty() {
+            buffer.push_str(&self.time_at(Utc::now(), &self.time_fmt));
             buffer.push('\n');
         }
     }
 
-    fn format_join(&self, buffer: &mut String, players: &Players) -> Result<(), Error> {
+    fn format_join(
+        &self,
+        buffer: &mut String,
+        common: &Context,
+        players: &Players,
+    ) -> Result<(), Error> {
         if !players.is_empty() {
-            Self::build_players(buffer, &self.join_fmt, players)?;
+            let mut context = common.clone();
+            Self::insert_players(&mut context, PlayerContext::from_players(players));
+            if let Some(message) = self.render("join_fmt", &context)? {
+                buffer.push_str(&message);
+            }
             buffer.push('\n');
         }
         Ok(())
     }
 
-    fn format_leave(&self, buffer: &mut String, players: &Players) -> Result<(), Error> {
+    fn format_leave(
+        &self,
+        buffer: &mut String,
+        common: &Context,
+        players: &Players,
+        sessions: &Sessions,
+    ) -> Result<(), Error> {
         if !players.is_empty() {
-            Self::build_players(buffer, &self.leave_fmt, players)?;
+            let mut context = common.clone();
+            Self::insert_players(
+                &mut context,
+                PlayerContext::with_sessions(players, sessions, |duration| {
+                    self.format_duration(duration)
+                }),
+            );
+            if let Some(message) = self.render("leave_fmt", &context)? {
+                buffer.push_str(&message);
+            }
             buffer.push('\n');
         }
         Ok(())
     }
 
-    fn format_current_players(
+    /// returns false if the format is empty, which means the change shouldn't be notified.
+    fn build_change(
         &self,
         buffer: &mut String,
-        online_count: u32,
-        players: &Players,
-    ) -> Result<(), Error> {
-        let mut hashmap = HashMap::new();
-        hashmap.insert("count".to_owned(), online_count.to_string());
-        Self::build_players_hashmap(buffer, &mut hashmap, &self.players_fmt, players)
+        name: &str,
+        context: &Context,
+    ) -> Result<bool, Error> {
+        match self.render(name, context)? {
+            Some(message) => {
+                buffer.push_str(&message);
+                Ok(true)
+            }
+            Option::None => Ok(false),
+        }
     }
 
-    fn build_players(buffer: &mut String, fmt: &str, players: &Players) -> Result<(), Error> {
-        let mut hashmap = HashMap::new();
-        Self::build_players_hashmap(buffer, &mut hashmap, fmt, players)
+    fn format_count(
+        &self,
+        buffer: &mut String,
+        common: &Context,
+        name: &str,
+        count: u32,
+    ) -> Result<(), Error> {
+        if count > 0 {
+            let mut context = common.clone();
+            context.insert("count", &count);
+            if let Some(message) = self.render(name, &context)? {
+                buffer.push_str(&message);
+            }
+            buffer.push('\n');
+        }
+        Ok(())
     }
 
-    fn build_players_hashmap(
+    fn format_current_players(
+        &self,
         buffer: &mut String,
-        hashmap: &mut HashMap<String, String>,
-        fmt: &str,
+        common: &Context,
+        online_count: u32,
         players: &Players,
     ) -> Result<(), Error> {
-        hashmap.insert("players".to_owned(), format!("{}", players));
-        buffer.push_str(&fmt.format(hashmap)?);
+        let mut context = common.clone();
+        context.insert("count", &online_count);
+        context.insert("players", &PlayerContext::from_players(players));
+        if let Some(message) = self.render("players_fmt", &context)? {
+            buffer.push_str(&message);
+        }
         Ok(())
     }
 }
@@ -134,19 +587,51 @@ impl StatusFormats {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::models::Player;
+    use crate::models::{Player, Session};
+    use chrono::TimeZone;
 
-    fn setup_format() -> StatusFormats {
-        StatusFormats {
+    fn setup_strings() -> FormatStrings {
+        FormatStrings {
             recover_msg: "recovered".to_owned(),
             down_msg: "down".to_owned(),
             join_fmt: "{players}".to_owned(),
             leave_fmt: "{players}".to_owned(),
+            join_count_fmt: "+{count}".to_owned(),
+            leave_count_fmt: "-{count}".to_owned(),
+            motd_change_fmt: "{old_motd} -> {motd}".to_owned(),
+            version_change_fmt: "{old_version} ({old_protocol}) -> {version} ({protocol})"
+                .to_owned(),
+            max_players_change_fmt: "".to_owned(),
+            count_reached_fmt: "{count} >= {threshold}".to_owned(),
+            full_fmt: "{count}/{max}".to_owned(),
+            empty_msg: "empty".to_owned(),
+            new_player_fmt: "welcome {players}".to_owned(),
+            digest_fmt: "{unique_count} ({players}) {peak_count}@{peak_time} {playtime} {outages}"
+                .to_owned(),
+            stopped_msg: "{hostname} stopped".to_owned(),
             players_fmt: "{players} {count}".to_owned(),
             time_fmt: "[]".to_owned(),
+            locale: Locale::English,
+            timezone: Timezone::Local,
+            duration_style: DurationStyle::Short,
         }
     }
 
+    fn setup_server() -> ServerContext {
+        ServerContext {
+            hostname: "localhost".to_owned(),
+            port: 25565,
+            max: Some(20),
+            version: Some("1.19".to_owned()),
+            motd: Some("A Minecraft Server".to_owned()),
+            latency: Some(42),
+        }
+    }
+
+    fn setup_format() -> StatusFormats {
+        StatusFormats::new(&setup_strings()).unwrap()
+    }
+
     #[test]
     fn status_format_player_change() {
         let format = setup_format();
@@ -160,14 +645,175 @@ mod tests {
             ]),
             joined_players: Players::from(vec![Player::new("idA", "A"), Player::new("idB", "B")]),
             left_players: Players::from(vec![Player::new("idD", "D")]),
+            finished_sessions: Sessions::from(vec![Session::new(
+                Player::new("idD", "D"),
+                chrono::Duration::minutes(5),
+            )]),
         };
 
         assert_eq!(
-            &format.format(&recover).unwrap().unwrap(),
+            &format.format(&recover, &setup_server()).unwrap().unwrap(),
             "[]\nA, B\nD\nA, B, C 3"
         );
     }
 
+    #[test]
+    fn status_format_leave_with_duration() {
+        let mut strings = setup_strings();
+        strings.leave_fmt = "{players_with_duration}".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::PlayerChange {
+            online_count: 0,
+            current_players: Players::default(),
+            joined_players: Players::default(),
+            left_players: Players::from(vec![Player::new("idA", "Alex")]),
+            finished_sessions: Sessions::from(vec![Session::new(
+                Player::new("idA", "Alex"),
+                chrono::Duration::minutes(133),
+            )]),
+        };
+
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nAlex (2h13m)\n 0"
+        );
+    }
+
+    #[test]
+    fn status_format_templates() {
+        let mut strings = setup_strings();
+        strings.join_fmt =
+            "{% for p in players %}{{ p.name }}{% if not loop.last %} & {% endif %}{% endfor %} joined"
+                .to_owned();
+        strings.players_fmt = "{{ count }} player{{ count | pluralize }} online".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::PlayerChange {
+            online_count: 1,
+            current_players: Players::from(vec![Player::new("idB", "B")]),
+            joined_players: Players::from(vec![Player::new("idB", "B"), Player::new("idA", "A")]),
+            left_players: Players::default(),
+            finished_sessions: Sessions::default(),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nA & B joined\n1 player online"
+        );
+
+        strings.full_fmt = "{{ count }/{{ max }}".to_owned();
+        assert!(StatusFormats::new(&strings).is_err());
+    }
+
+    #[test]
+    fn status_format_rejects_unknown_placeholders() {
+        let mut strings = setup_strings();
+        strings.players_fmt = "{players} ({latency}ms, {version}, {motd}, max {max})".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::ServerEmpty;
+        assert!(format.format(&message, &setup_server()).is_ok());
+
+        strings.full_fmt = "{count}/{maximum}".to_owned();
+        match StatusFormats::new(&strings) {
+            Err(Error::FormatError(reason)) => assert!(reason.contains("maximum")),
+            Ok(_) => panic!("expected an error"),
+        }
+
+        strings.full_fmt = "{count}/{max}".to_owned();
+        strings.join_fmt =
+            "{% for p in players %}{{ p.name }}{{ player.name }}{% endfor %}".to_owned();
+        assert!(StatusFormats::new(&strings).is_err());
+    }
+
+    #[test]
+    fn status_format_common_variables() {
+        let mut strings = setup_strings();
+        strings.recover_msg =
+            "recovered{% if downtime %} after {{ downtime }}{% endif %}".to_owned();
+        strings.down_msg = "{hostname}:{port} is down: {reason}".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::Recover {
+            online_count: 0,
+            current_players: Players::default(),
+            downtime: Some(chrono::Duration::minutes(10)),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nrecovered after 10m\n 0"
+        );
+
+        let message = StatusDifference::Recover {
+            online_count: 0,
+            current_players: Players::default(),
+            downtime: None,
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nrecovered\n 0"
+        );
+
+        let message = StatusDifference::Down {
+            reason: String::from("hoge"),
+            finished_sessions: Sessions::default(),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nlocalhost:25565 is down: hoge"
+        );
+    }
+
+    #[test]
+    fn status_format_player_count_change() {
+        let format = setup_format();
+
+        let message = StatusDifference::PlayerCountChange {
+            online_count: 23,
+            current_players: Players::from(vec![Player::new("idA", "A")]),
+            joined_count: 3,
+            left_count: 0,
+        };
+
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\n+3\nA 23"
+        );
+    }
+
+    #[test]
+    fn status_format_localized_defaults() {
+        let message = StatusDifference::PlayerCountChange {
+            online_count: 1,
+            current_players: Players::from(vec![Player::new("idA", "A")]),
+            joined_count: 1,
+            left_count: 0,
+        };
+
+        let localized = |locale: Locale| {
+            let mut strings = setup_strings();
+            strings.join_count_fmt = locale.default_format("join_count_fmt").to_owned();
+            strings.players_fmt = locale.default_format("players_fmt").to_owned();
+            strings.locale = locale;
+            StatusFormats::new(&strings).unwrap()
+        };
+
+        assert_eq!(
+            &localized(Locale::English)
+                .format(&message, &setup_server())
+                .unwrap()
+                .unwrap(),
+            "[]\n1 player joined.\n1 player online: A"
+        );
+        assert_eq!(
+            &localized(Locale::Japanese)
+                .format(&message, &setup_server())
+                .unwrap()
+                .unwrap(),
+            "[]\n1人が参加しました。\nオンライン1人: A"
+        );
+    }
+
     #[test]
     fn status_format_recover() {
         let format = setup_format();
@@ -179,10 +825,11 @@ mod tests {
                 Player::new("idB", "B"),
                 Player::new("idC", "C"),
             ]),
+            downtime: Some(chrono::Duration::minutes(10)),
         };
 
         assert_eq!(
-            &format.format(&message).unwrap().unwrap(),
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
             "[]\nrecovered\nA, B, C 3"
         );
     }
@@ -193,7 +840,197 @@ mod tests {
 
         let message = StatusDifference::Down {
             reason: String::from("hoge"),
+            finished_sessions: Sessions::default(),
         };
-        assert_eq!(&format.format(&message).unwrap().unwrap(), "[]\ndown");
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\ndown"
+        );
+    }
+
+    #[test]
+    fn status_format_server_info_change() {
+        let format = setup_format();
+
+        let message = StatusDifference::MotdChange {
+            old_motd: "A".to_owned(),
+            motd: "B".to_owned(),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nA -> B"
+        );
+
+        let message = StatusDifference::VersionChange {
+            old_version: "1.18".to_owned(),
+            version: "1.19".to_owned(),
+            old_protocol: 757,
+            protocol: 759,
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\n1.18 (757) -> 1.19 (759)"
+        );
+
+        let message = StatusDifference::MaxPlayersChange {
+            old_max: 20,
+            max: 30,
+        };
+        assert_eq!(format.format(&message, &setup_server()).unwrap(), None);
+    }
+
+    #[test]
+    fn status_format_count_events() {
+        let format = setup_format();
+
+        let message = StatusDifference::PlayerCountReached {
+            threshold: 5,
+            online_count: 6,
+            max: 20,
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\n6 >= 5"
+        );
+
+        let message = StatusDifference::ServerFull {
+            online_count: 20,
+            max: 20,
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\n20/20"
+        );
+
+        let message = StatusDifference::ServerEmpty;
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nempty"
+        );
+    }
+
+    #[test]
+    fn status_format_new_player() {
+        let format = setup_format();
+
+        let message = StatusDifference::NewPlayer {
+            players: Players::from(vec![Player::new("idS", "Steve")]),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nwelcome Steve"
+        );
+    }
+
+    #[test]
+    fn status_format_digest() {
+        let format = setup_format();
+
+        let now = chrono::Utc::now();
+        let digest = Digest {
+            since: now - chrono::Duration::days(1),
+            until: now,
+            players: Players::from(vec![Player::new("idA", "A"), Player::new("idB", "B")]),
+            peak: Option::None,
+            playtime: chrono::Duration::minutes(133),
+            outages: 1,
+        };
+
+        assert_eq!(
+            &format.format_digest(&digest, &setup_server()).unwrap(),
+            "[]\n2 (A, B) 0@- 2h13m 1"
+        );
+    }
+
+    #[test]
+    fn status_format_stopped() {
+        let format = setup_format();
+        assert_eq!(
+            format.format_stopped(&setup_server()).unwrap(),
+            Some("localhost stopped".to_owned())
+        );
+
+        let mut strings = setup_strings();
+        strings.stopped_msg = "".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+        assert_eq!(
+            format.format_stopped(&setup_server()).unwrap(),
+            Option::None
+        );
+    }
+
+    #[test]
+    fn status_format_places_time() {
+        let mut strings = setup_strings();
+        strings.join_fmt = "{players} joined at {time}".to_owned();
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::PlayerChange {
+            online_count: 1,
+            current_players: Players::from(vec![Player::new("idA", "A")]),
+            joined_players: Players::from(vec![Player::new("idA", "A")]),
+            left_players: Players::default(),
+            finished_sessions: Sessions::default(),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "A joined at []\nA 1"
+        );
+
+        // the messages without `join_fmt` still get the time at the top
+        let message = StatusDifference::PlayerChange {
+            online_count: 0,
+            current_players: Players::default(),
+            joined_players: Players::default(),
+            left_players: Players::from(vec![Player::new("idA", "A")]),
+            finished_sessions: Sessions::default(),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nA\n 0"
+        );
+
+        strings.time_fmt = "%-m月%-d日(%a) %H:%M".to_owned();
+        strings.locale = Locale::Japanese;
+        strings.timezone = "Asia/Tokyo".parse().unwrap();
+        let format = StatusFormats::new(&strings).unwrap();
+        assert_eq!(
+            format.time_at(Utc.ymd(2022, 6, 4).and_hms(15, 4, 0), &strings.time_fmt),
+            "6月5日(日) 00:04"
+        );
+    }
+
+    #[test]
+    fn status_format_long_durations() {
+        let mut strings = setup_strings();
+        strings.leave_fmt = "{{ players | names(with_duration=true) }} left.".to_owned();
+        strings.recover_msg = "recovered after {downtime}".to_owned();
+        strings.duration_style = DurationStyle::Long;
+        let format = StatusFormats::new(&strings).unwrap();
+
+        let message = StatusDifference::PlayerChange {
+            online_count: 0,
+            current_players: Players::default(),
+            joined_players: Players::default(),
+            left_players: Players::from(vec![Player::new("idA", "Alex")]),
+            finished_sessions: Sessions::from(vec![Session::new(
+                Player::new("idA", "Alex"),
+                chrono::Duration::minutes(133),
+            )]),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nAlex (2 hours 13 minutes) left.\n 0"
+        );
+
+        let message = StatusDifference::Recover {
+            online_count: 0,
+            current_players: Players::default(),
+            downtime: Some(chrono::Duration::minutes(10)),
+        };
+        assert_eq!(
+            &format.format(&message, &setup_server()).unwrap().unwrap(),
+            "[]\nrecovered after 10 minutes\n 0"
+        );
     }
 }
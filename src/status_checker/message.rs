@@ -0,0 +1,81 @@
+use super::{ServerContext, StatusDifference};
+use crate::notifier::Message;
+
+impl StatusDifference {
+    /// the message carrying `body` and what the difference tells.
+    pub fn to_message(&self, body: &str, server: &ServerContext) -> Message {
+        use self::StatusDifference::*;
+
+        let message = Message::new(body)
+            .with_event(self.kind())
+            .with_server(server);
+
+        match *self {
+            PlayerChange {
+                online_count,
+                ref current_players,
+                ref joined_players,
+                ref left_players,
+                ..
+            } => message
+                .with_joined_players(joined_players)
+                .with_left_players(left_players)
+                .with_current_players(current_players, online_count),
+            PlayerCountChange {
+                online_count,
+                ref current_players,
+                joined_count,
+                left_count,
+            } => message
+                .with_current_players(current_players, online_count)
+                .with_counts(joined_count, left_count),
+            Recover {
+                online_count,
+                ref current_players,
+                ..
+            } => message.with_current_players(current_players, online_count),
+            PlayerCountReached { online_count, .. } | ServerFull { online_count, .. } => {
+                message.with_online_count(online_count)
+            }
+            NewPlayer { ref players } => message.with_joined_players(players),
+            _ => message,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Player, Players, Sessions};
+
+    #[test]
+    fn status_difference_to_message() {
+        let server = ServerContext {
+            hostname: "localhost".to_owned(),
+            port: 25565,
+            ..ServerContext::default()
+        };
+
+        let difference = StatusDifference::PlayerChange {
+            online_count: 2,
+            current_players: Players::from(vec![Player::new("idA", "A"), Player::new("idB", "B")]),
+            joined_players: Players::from(vec![Player::new("idB", "B")]),
+            left_players: Players::from(vec![Player::new("idC", "C")]),
+            finished_sessions: Sessions::default(),
+        };
+        let message = difference.to_message("B joined.", &server);
+
+        assert_eq!(message.event(), "player_change");
+        assert_eq!(message.body(), "B joined.");
+        assert_eq!(message.server(), Some(&server));
+        assert_eq!(message.joined_players(), &[Player::new("idB", "B")]);
+        assert_eq!(message.left_players(), &[Player::new("idC", "C")]);
+        assert_eq!(message.current_players().len(), 2);
+        assert_eq!(message.online_count(), Some(2));
+
+        let message = StatusDifference::ServerEmpty.to_message("Everyone left.", &server);
+        assert_eq!(message.event(), "empty");
+        assert!(message.current_players().is_empty());
+        assert_eq!(message.online_count(), None);
+    }
+}
@@ -1,5 +1,19 @@
 mod checker;
+mod coalescer;
+mod count_triggers;
 mod formats;
+mod message;
+mod player_filter;
+mod player_registry;
+mod polling_interval;
+mod session_tracker;
+mod state_file;
 
-pub use self::checker::{Status, StatusChecker, StatusDifference};
-pub use self::formats::{Error as FormatError, StatusFormats};
+pub use self::checker::{Status, StatusChecker, StatusDifference, Thresholds};
+pub use self::coalescer::Coalescer;
+pub use self::count_triggers::CountRules;
+pub use self::formats::{Error as FormatError, FormatStrings, ServerContext, StatusFormats};
+pub use self::player_filter::{PlayerFilter, PlayerOverride};
+pub use self::player_registry::{Error as RegistryError, PlayerRegistry, RegistryEntry};
+pub use self::polling_interval::PollingInterval;
+pub use self::state_file::{CheckerState, Error as StateFileError, StateFile};
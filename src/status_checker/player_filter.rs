@@ -0,0 +1,226 @@
+use super::StatusDifference;
+use crate::models::{Player, Players, Session, Sessions};
+
+/// overrides how a specific player is announced.
+#[derive(Clone, Debug)]
+pub struct PlayerOverride {
+    /// the name or the UUID of the player.
+    pub player: String,
+    /// announce (or hide) the player regardless of the allow-list and the ignore-list.
+    pub announce: Option<bool>,
+    /// a name shown instead of the player name.
+    pub display_name: Option<String>,
+}
+
+/// decides which players are announced, by names or UUIDs.
+#[derive(Clone, Debug, Default)]
+pub struct PlayerFilter {
+    /// announce only these players if not empty.
+    only: Vec<String>,
+    /// never announce these players.
+    ignore: Vec<String>,
+    overrides: Vec<PlayerOverride>,
+}
+
+impl PlayerFilter {
+    pub fn new(only: Vec<String>, ignore: Vec<String>, overrides: Vec<PlayerOverride>) -> Self {
+        Self {
+            only,
+            ignore,
+            overrides,
+        }
+    }
+
+    /// player names are case-insensitive, and UUIDs may be written with or without hyphens.
+    fn matches(pattern: &str, player: &Player) -> bool {
+        let normalize = |s: &str| s.replace('-', "").to_lowercase();
+
+        pattern.eq_ignore_ascii_case(player.name()) || normalize(pattern) == normalize(player.id())
+    }
+
+    fn find_override(&self, player: &Player) -> Option<&PlayerOverride> {
+        self.overrides
+            .iter()
+            .find(|o| Self::matches(&o.player, player))
+    }
+
+    pub fn is_announced(&self, player: &Player) -> bool {
+        if let Some(announce) = self.find_override(player).and_then(|o| o.announce) {
+            return announce;
+        }
+
+        if self.ignore.iter().any(|p| Self::matches(p, player)) {
+            return false;
+        }
+
+        self.only.is_empty() || self.only.iter().any(|p| Self::matches(p, player))
+    }
+
+    fn display(&self, player: &Player) -> Player {
+        match self
+            .find_override(player)
+            .and_then(|o| o.display_name.as_ref())
+        {
+            Some(display_name) => Player::new(player.id(), display_name),
+            None => player.clone(),
+        }
+    }
+
+    pub fn filter_players(&self, players: &Players) -> Players {
+        players
+            .iter()
+            .filter(|player| self.is_announced(player))
+            .map(|player| self.display(player))
+            .collect()
+    }
+
+    pub fn filter_sessions(&self, sessions: &Sessions) -> Sessions {
+        let sessions: Vec<Session> = sessions
+            .iter()
+            .filter(|session| self.is_announced(session.player()))
+            .map(|session| Session::new(self.display(session.player()), session.duration()))
+            .collect();
+
+        Sessions::from(sessions)
+    }
+
+    /// removes players who shouldn't be announced from a difference.
+    /// `online_count` is kept as it is, so that `{count}` shows the actual number.
+    /// returns `None` if nobody is left to announce in a `PlayerChange` or a `NewPlayer`.
+    pub fn apply(&self, difference: StatusDifference) -> Option<StatusDifference> {
+        use self::StatusDifference::*;
+
+        match difference {
+            PlayerChange {
+                online_count,
+                current_players,
+                joined_players,
+                left_players,
+                finished_sessions,
+            } => {
+                let joined_players = self.filter_players(&joined_players);
+                let left_players = self.filter_players(&left_players);
+
+                if joined_players.is_empty() && left_players.is_empty() {
+                    return Option::None;
+                }
+
+                Some(PlayerChange {
+                    online_count,
+                    current_players: self.filter_players(&current_players),
+                    joined_players,
+                    left_players,
+                    finished_sessions: self.filter_sessions(&finished_sessions),
+                })
+            }
+            PlayerCountChange {
+                online_count,
+                current_players,
+                joined_count,
+                left_count,
+            } => Some(PlayerCountChange {
+                online_count,
+                current_players: self.filter_players(&current_players),
+                joined_count,
+                left_count,
+            }),
+            Recover {
+                online_count,
+                current_players,
+                downtime,
+            } => Some(Recover {
+                online_count,
+                current_players: self.filter_players(&current_players),
+                downtime,
+            }),
+            Down {
+                reason,
+                finished_sessions,
+            } => Some(Down {
+                reason,
+                finished_sessions: self.filter_sessions(&finished_sessions),
+            }),
+            NewPlayer { players } => {
+                let players = self.filter_players(&players);
+                if players.is_empty() {
+                    Option::None
+                } else {
+                    Some(NewPlayer { players })
+                }
+            }
+            difference => Some(difference),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup_filter() -> PlayerFilter {
+        PlayerFilter::new(
+            Vec::new(),
+            vec!["AFKBot".to_owned()],
+            vec![PlayerOverride {
+                player: "069a79f4-44e9-4726-a5be-fca90e38aaf5".to_owned(),
+                announce: None,
+                display_name: Some("Notch (owner)".to_owned()),
+            }],
+        )
+    }
+
+    #[test]
+    fn player_filter_ignores_players() {
+        let filter = setup_filter();
+
+        let players = Players::from(vec![
+            Player::new("idA", "A"),
+            Player::new("idBot", "afkbot"),
+            Player::new("069a79f444e94726a5befca90e38aaf5", "Notch"),
+        ]);
+
+        assert_eq!(
+            format!("{}", filter.filter_players(&players)),
+            "A, Notch (owner)"
+        );
+    }
+
+    #[test]
+    fn player_filter_allow_list() {
+        let filter = PlayerFilter::new(
+            vec!["A".to_owned(), "B".to_owned()],
+            Vec::new(),
+            vec![PlayerOverride {
+                player: "B".to_owned(),
+                announce: Some(false),
+                display_name: None,
+            }],
+        );
+
+        let players = Players::from(vec![
+            Player::new("idA", "A"),
+            Player::new("idB", "B"),
+            Player::new("idC", "C"),
+        ]);
+
+        assert_eq!(format!("{}", filter.filter_players(&players)), "A");
+    }
+
+    #[test]
+    fn player_filter_drops_ignored_changes() {
+        let filter = setup_filter();
+
+        let difference = StatusDifference::PlayerChange {
+            online_count: 2,
+            current_players: Players::from(vec![
+                Player::new("idA", "A"),
+                Player::new("idBot", "AFKBot"),
+            ]),
+            joined_players: Players::from(vec![Player::new("idBot", "AFKBot")]),
+            left_players: Players::default(),
+            finished_sessions: Sessions::default(),
+        };
+
+        assert!(filter.apply(difference).is_none());
+    }
+}
@@ -0,0 +1,178 @@
+extern crate serde_json;
+
+use crate::models::{Player, Players};
+use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::{convert, io};
+
+#[derive(Debug)]
+pub enum Error {
+    XDGError(xdg_basedir::Error),
+    IoError(io::Error),
+    JsonError(serde_json::Error),
+}
+
+impl_convert_for_error!(xdg_basedir::Error, Error::XDGError);
+impl_convert_for_error!(io::Error, Error::IoError);
+impl_convert_for_error!(serde_json::Error, Error::JsonError);
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RegistryEntry {
+    /// the latest name of the player
+    pub name: String,
+    pub first_seen: DateTime<Utc>,
+    pub last_seen: DateTime<Utc>,
+}
+
+/// every player who has ever been seen on the server, keyed by UUID.
+#[derive(Default)]
+pub struct PlayerRegistry {
+    path: Option<PathBuf>,
+    entries: BTreeMap<String, RegistryEntry>,
+}
+
+impl PlayerRegistry {
+    /// loads the registry, or creates an empty one if the file doesn't exist yet.
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        let entries = match File::open(path) {
+            Ok(mut file) => {
+                let mut string = String::with_capacity(256);
+                file.read_to_string(&mut string)?;
+                serde_json::from_str(&string)?
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        Ok(Self {
+            path: Some(path.to_owned()),
+            entries,
+        })
+    }
+
+    /// `$XDG_DATA_HOME/mcnotify/players_<hostname>_<port>.json`
+    pub fn default_path(hostname: &str, port: u16) -> Result<PathBuf, Error> {
+        const DATA_DIR: &str = "mcnotify";
+
+        let mut pathbuf = xdg_basedir::get_data_home()?;
+        pathbuf.push(DATA_DIR);
+        pathbuf.push(format!("players_{}_{}.json", hostname, port));
+
+        Ok(pathbuf)
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &RegistryEntry)> {
+        self.entries.iter()
+    }
+
+    /// updates `last_seen` of the players and returns the players seen for the first time.
+    /// nobody is returned while the registry is empty, not to welcome everyone on the first run.
+    pub fn record(&mut self, players: &Players, at: DateTime<Utc>) -> Players {
+        let is_first_run = self.entries.is_empty();
+        let mut new_players: Vec<Player> = Vec::new();
+
+        for player in players.iter() {
+            match self.entries.get_mut(player.id()) {
+                Some(entry) => {
+                    entry.name = player.name().clone();
+                    entry.last_seen = at;
+                }
+                None => {
+                    self.entries.insert(
+                        player.id().clone(),
+                        RegistryEntry {
+                            name: player.name().clone(),
+                            first_seen: at,
+                            last_seen: at,
+                        },
+                    );
+                    new_players.push(player.clone());
+                }
+            }
+        }
+
+        if is_first_run {
+            Players::default()
+        } else {
+            Players::from(new_players)
+        }
+    }
+
+    /// removes the players, so they are seen for the first time again.
+    pub fn forget(&mut self, players: &Players) {
+        for player in players.iter() {
+            self.entries.remove(player.id());
+        }
+    }
+
+    /// removes players who have not been seen since `before`, and returns how many were removed.
+    pub fn prune(&mut self, before: DateTime<Utc>) -> usize {
+        let len = self.entries.len();
+        self.entries.retain(|_, entry| entry.last_seen >= before);
+
+        len - self.entries.len()
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = match self.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string_pretty(&self.entries)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn player_registry_detects_new_players() {
+        let mut registry = PlayerRegistry::default();
+        let now = Utc::now();
+
+        let players = Players::from(vec![Player::new("idA", "A")]);
+        assert!(registry.record(&players, now).is_empty());
+
+        let players = Players::from(vec![Player::new("idA", "A"), Player::new("idB", "B")]);
+        assert_eq!(
+            registry.record(&players, now),
+            Players::from(vec![Player::new("idB", "B")])
+        );
+        assert!(registry.record(&players, now).is_empty());
+
+        // a player who was forgotten is new again
+        let forgotten = Players::from(vec![Player::new("idB", "B")]);
+        registry.forget(&forgotten);
+        assert_eq!(registry.record(&players, now), forgotten);
+    }
+
+    #[test]
+    fn player_registry_prune() {
+        let mut registry = PlayerRegistry::default();
+        let now = Utc::now();
+
+        registry.record(&Players::from(vec![Player::new("idA", "A")]), now);
+        registry.record(
+            &Players::from(vec![Player::new("idB", "B")]),
+            now + chrono::Duration::days(30),
+        );
+
+        assert_eq!(registry.prune(now + chrono::Duration::days(1)), 1);
+        assert_eq!(registry.entries().count(), 1);
+    }
+}
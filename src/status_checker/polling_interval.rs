@@ -0,0 +1,84 @@
+use super::Status;
+use std::time::Duration;
+
+/// decides how long to wait until the next check.
+/// `min` is used while players are online or right after a change,
+/// the interval grows by `base` while the server is empty, and doubles while the server is down.
+pub struct PollingInterval {
+    base: Duration,
+    min: Duration,
+    max: Duration,
+    current: Duration,
+}
+
+impl PollingInterval {
+    pub fn new(base: Duration, min: Duration, max: Duration) -> Self {
+        let min = min.min(base);
+        let max = max.max(base);
+
+        Self {
+            base,
+            min,
+            max,
+            current: base,
+        }
+    }
+
+    /// `changed` is whether the last check produced any event.
+    pub fn next(&mut self, status: &Status, changed: bool) -> Duration {
+        let next = match *status {
+            _ if changed => self.min,
+            Status::Available { online_count, .. } if online_count > 0 => self.min,
+            Status::Available { .. } => self.current + self.base,
+            Status::Unavailable { .. } => self.current * 2,
+        };
+
+        self.current = next.max(self.min).min(self.max);
+        self.current
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Players, ServerInfo};
+
+    fn available(online_count: u32) -> Status {
+        Status::Available {
+            online_count,
+            current_players: Players::default(),
+            sample_complete: true,
+            server_info: ServerInfo::default(),
+            latency: Duration::default(),
+        }
+    }
+
+    fn secs(secs: u64) -> Duration {
+        Duration::from_secs(secs)
+    }
+
+    #[test]
+    fn polling_interval_adapts_to_status() {
+        let mut interval = PollingInterval::new(secs(60), secs(10), secs(300));
+
+        assert_eq!(interval.next(&available(2), false), secs(10));
+        assert_eq!(interval.next(&available(0), true), secs(10));
+        assert_eq!(interval.next(&available(0), false), secs(70));
+        assert_eq!(interval.next(&available(0), false), secs(130));
+
+        let unavailable = Status::Unavailable {
+            reason: "timeout".to_owned(),
+        };
+        assert_eq!(interval.next(&unavailable, false), secs(260));
+        assert_eq!(interval.next(&unavailable, false), secs(300));
+        assert_eq!(interval.next(&available(1), false), secs(10));
+    }
+
+    #[test]
+    fn polling_interval_is_fixed_without_bounds() {
+        let mut interval = PollingInterval::new(secs(60), secs(60), secs(60));
+
+        assert_eq!(interval.next(&available(2), true), secs(60));
+        assert_eq!(interval.next(&available(0), false), secs(60));
+    }
+}
@@ -0,0 +1,135 @@
+use crate::models::{Player, Players, Session, Sessions};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::HashMap;
+
+/// remembers when each online player joined the server.
+#[derive(Default)]
+pub struct SessionTracker {
+    started_at: HashMap<Player, DateTime<Utc>>,
+    /// players who left but may come back within the grace period:
+    /// when their sessions started, and when they left.
+    away: HashMap<Player, (Option<DateTime<Utc>>, DateTime<Utc>)>,
+}
+
+impl From<Vec<(Player, DateTime<Utc>)>> for SessionTracker {
+    fn from(snapshot: Vec<(Player, DateTime<Utc>)>) -> Self {
+        Self {
+            started_at: snapshot.into_iter().collect(),
+            away: HashMap::new(),
+        }
+    }
+}
+
+impl SessionTracker {
+    pub fn start(&mut self, players: &Players, at: DateTime<Utc>) {
+        for player in players.iter() {
+            self.started_at.entry(player.clone()).or_insert(at);
+        }
+    }
+
+    pub fn finish(&mut self, players: &Players, at: DateTime<Utc>) -> Sessions {
+        let sessions: Vec<Session> = players
+            .iter()
+            .filter_map(|player| {
+                let started_at = self.started_at.remove(player)?;
+                Some(Session::new(player.clone(), at - started_at))
+            })
+            .collect();
+
+        Sessions::from(sessions)
+    }
+
+    /// keeps the sessions of the players who left, to be resumed by `resume` or finished by `expire`.
+    pub fn leave(&mut self, players: &Players, at: DateTime<Utc>) {
+        for player in players.iter() {
+            let started_at = self.started_at.remove(player);
+            self.away.insert(player.clone(), (started_at, at));
+        }
+    }
+
+    /// continues the sessions of the players who came back, and returns them.
+    pub fn resume(&mut self, players: &Players) -> Players {
+        players
+            .iter()
+            .filter(|player| match self.away.remove(player) {
+                Some((started_at, _)) => {
+                    if let Some(started_at) = started_at {
+                        self.started_at.insert((*player).clone(), started_at);
+                    }
+                    true
+                }
+                None => false,
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// finishes the sessions of the players who have been away for `grace` at least,
+    /// at the time when they left.
+    pub fn expire(&mut self, grace: Duration, now: DateTime<Utc>) -> (Players, Sessions) {
+        let expired: Vec<Player> = self
+            .away
+            .iter()
+            .filter(|(_, (_, left_at))| now - *left_at >= grace)
+            .map(|(player, _)| player.clone())
+            .collect();
+
+        let sessions: Vec<Session> = expired
+            .iter()
+            .filter_map(|player| {
+                let (started_at, left_at) = self.away.remove(player)?;
+                Some(Session::new(player.clone(), left_at - started_at?))
+            })
+            .collect();
+
+        (Players::from(expired), Sessions::from(sessions))
+    }
+
+    /// returns the players who are away in a form which can be serialized.
+    pub fn away_snapshot(&self) -> Vec<(Player, Option<DateTime<Utc>>, DateTime<Utc>)> {
+        self.away
+            .iter()
+            .map(|(player, (started_at, left_at))| (player.clone(), *started_at, *left_at))
+            .collect()
+    }
+
+    pub fn with_away(mut self, away: Vec<(Player, Option<DateTime<Utc>>, DateTime<Utc>)>) -> Self {
+        self.away = away
+            .into_iter()
+            .map(|(player, started_at, left_at)| (player, (started_at, left_at)))
+            .collect();
+        self
+    }
+
+    /// returns join times in a form which can be serialized.
+    pub fn snapshot(&self) -> Vec<(Player, DateTime<Utc>)> {
+        self.started_at
+            .iter()
+            .map(|(player, started_at)| (player.clone(), *started_at))
+            .collect()
+    }
+
+    /// forgets the sessions of the players online, keeping the ones of the players who are away
+    /// until they expire.
+    pub fn forget_active(&mut self) {
+        self.started_at.clear();
+    }
+
+    /// finishes every session, including the ones of the players who are away.
+    pub fn finish_all(&mut self, at: DateTime<Utc>) -> Sessions {
+        let away = self
+            .away
+            .drain()
+            .filter_map(|(player, (started_at, left_at))| {
+                Some(Session::new(player, left_at - started_at?))
+            });
+        let sessions: Vec<Session> = self
+            .started_at
+            .drain()
+            .map(|(player, started_at)| Session::new(player, at - started_at))
+            .chain(away)
+            .collect();
+
+        Sessions::from(sessions)
+    }
+}
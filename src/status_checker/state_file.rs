@@ -0,0 +1,156 @@
+extern crate serde_json;
+
+use super::Status;
+use crate::models::{Player, ServerInfo};
+use chrono::{DateTime, Utc};
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::{convert, env, io};
+
+#[derive(Debug)]
+pub enum Error {
+    IoError(io::Error),
+    JsonError(serde_json::Error),
+    StateDirNotFound,
+}
+
+impl_convert_for_error!(io::Error, Error::IoError);
+impl_convert_for_error!(serde_json::Error, Error::JsonError);
+
+/// a snapshot of `StatusChecker` which is kept across restarts.
+#[derive(Serialize, Deserialize)]
+pub struct CheckerState {
+    pub saved_at: DateTime<Utc>,
+    pub status: Status,
+    pub sessions: Vec<(Player, DateTime<Utc>)>,
+    pub server_info: Option<ServerInfo>,
+    /// when the server went down, if it is down
+    #[serde(default)]
+    pub down_since: Option<DateTime<Utc>>,
+    /// the players who left within the rejoin grace period:
+    /// when their sessions started, and when they left
+    #[serde(default)]
+    pub away: Vec<(Player, Option<DateTime<Utc>>, DateTime<Utc>)>,
+}
+
+pub struct StateFile {
+    path: PathBuf,
+}
+
+impl StateFile {
+    pub fn new(path: &Path) -> Self {
+        Self {
+            path: path.to_owned(),
+        }
+    }
+
+    /// `$XDG_STATE_HOME/mcnotify/<hostname>_<port>.json`
+    pub fn default_path(hostname: &str, port: u16) -> Result<PathBuf, Error> {
+        const STATE_DIR: &str = "mcnotify";
+
+        let mut pathbuf = Self::state_home()?;
+        pathbuf.push(STATE_DIR);
+        pathbuf.push(format!("{}_{}.json", hostname, port));
+
+        Ok(pathbuf)
+    }
+
+    fn state_home() -> Result<PathBuf, Error> {
+        if let Some(state_home) = env::var_os("XDG_STATE_HOME") {
+            let pathbuf = PathBuf::from(state_home);
+            if pathbuf.is_absolute() {
+                return Ok(pathbuf);
+            }
+        }
+
+        let mut pathbuf = PathBuf::from(env::var_os("HOME").ok_or(Error::StateDirNotFound)?);
+        pathbuf.push(".local");
+        pathbuf.push("state");
+
+        Ok(pathbuf)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// returns `None` if the state has not been saved yet or is older than `max_age`.
+    pub fn load(
+        &self,
+        max_age: Duration,
+        now: DateTime<Utc>,
+    ) -> Result<Option<CheckerState>, Error> {
+        let mut file = match File::open(&self.path) {
+            Ok(file) => file,
+            Err(ref e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(Error::from(e)),
+        };
+
+        let mut string = String::with_capacity(256);
+        file.read_to_string(&mut string)?;
+
+        let state: CheckerState = serde_json::from_str(&string)?;
+        let age = (now - state.saved_at).to_std().unwrap_or_default();
+
+        if age > max_age {
+            Ok(None)
+        } else {
+            Ok(Some(state))
+        }
+    }
+
+    pub fn save(&self, state: &CheckerState) -> Result<(), Error> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        // write into a temporary file first not to leave a broken file on crash
+        let tmp_path = self.path.with_extension("json.tmp");
+        let mut file = File::create(&tmp_path)?;
+        file.write_all(serde_json::to_string(state)?.as_bytes())?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Players;
+
+    #[test]
+    fn state_file_ignores_stale_state() {
+        let mut path = env::temp_dir();
+        path.push(format!("mcnotify-state-{}.json", std::process::id()));
+        let state_file = StateFile::new(&path);
+
+        let now = Utc::now();
+        let state = CheckerState {
+            saved_at: now,
+            status: Status::Available {
+                online_count: 0,
+                current_players: Players::default(),
+                sample_complete: true,
+                server_info: ServerInfo::default(),
+                latency: Duration::default(),
+            },
+            sessions: Vec::new(),
+            server_info: None,
+            down_since: None,
+            away: Vec::new(),
+        };
+        state_file.save(&state).unwrap();
+
+        let max_age = Duration::from_secs(600);
+        let fresh = state_file.load(max_age, now + chrono::Duration::minutes(5));
+        let stale = state_file.load(max_age, now + chrono::Duration::minutes(15));
+        fs::remove_file(&path).unwrap();
+
+        assert!(fresh.unwrap().is_some());
+        assert!(stale.unwrap().is_none());
+    }
+}
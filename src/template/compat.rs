@@ -0,0 +1,147 @@
+/// the expressions which the legacy placeholders are replaced with.
+/// the other placeholders are replaced with the variables of the same names.
+const LEGACY_PLACEHOLDERS: &[(&str, &str)] = &[
+    ("players", "players | names"),
+    (
+        "players_with_duration",
+        "players | names(with_duration=true)",
+    ),
+];
+
+/// true if the source is written in the template syntax, not in the legacy `{name}` format.
+/// a legacy format with placeholders may have `{{` and `}}` as literal braces.
+pub fn is_template(source: &str) -> bool {
+    let legacy = legacy_placeholders(source).map_or(false, |names| !names.is_empty());
+    !legacy && (source.contains("{{") || source.contains("{%"))
+}
+
+/// the names of the placeholders if the source is valid in the legacy syntax of strfmt,
+/// where `{{` and `}}` are literal braces.
+fn legacy_placeholders(source: &str) -> Option<Vec<&str>> {
+    let mut names = Vec::new();
+    let mut rest = source;
+
+    while let Some(at) = rest.find(|c| c == '{' || c == '}') {
+        rest = &rest[at..];
+        if rest.starts_with("{{") || rest.starts_with("}}") {
+            rest = &rest[2..];
+            continue;
+        }
+        if rest.starts_with('}') {
+            return None;
+        }
+
+        let end = rest.find('}')?;
+        let inner = &rest[1..end];
+        let name = inner.split_once(':').map_or(inner, |(name, _)| name);
+        if !is_identifier(name) {
+            return None;
+        }
+        names.push(name);
+        rest = &rest[end + 1..];
+    }
+
+    Some(names)
+}
+
+/// converts a format in the legacy `{name}` syntax into a template,
+/// e.g. `{players} joined.` into `{{ players | names }} joined.`.
+/// a source which is already a template is returned as it is.
+/// a format spec of strfmt such as `{count:>3}` is an error, not to change the output silently.
+pub fn upgrade(source: &str) -> Result<String, String> {
+    if is_template(source) {
+        return Ok(source.to_owned());
+    }
+
+    let mut template = String::with_capacity(source.len() * 2);
+    let mut rest = source;
+
+    while let Some(start) = rest.find('{') {
+        template.push_str(&rest[..start].replace("}}", "}"));
+        rest = &rest[start..];
+
+        if rest.starts_with("{{") {
+            template.push_str("{{ \"{\" }}");
+            rest = &rest[2..];
+            continue;
+        }
+
+        let inner = rest[1..].find('}').map(|end| &rest[1..end + 1]);
+        if let Some((name, spec)) = inner.and_then(|inner| inner.split_once(':')) {
+            if is_identifier(name) {
+                return Err(format!(
+                    "the format spec `{{{}:{}}}` is not supported anymore, \
+                     please use `{{{}}}` or a template",
+                    name, spec, name
+                ));
+            }
+        }
+        let placeholder = inner.filter(|name| is_identifier(name));
+
+        match placeholder {
+            Some(name) => {
+                let expression = LEGACY_PLACEHOLDERS
+                    .iter()
+                    .find(|(legacy, _)| *legacy == name)
+                    .map_or(name, |(_, expression)| *expression);
+                template.push_str(&format!("{{{{ {} }}}}", expression));
+                rest = &rest[rest.find('}').unwrap_or_default() + 1..];
+            }
+            None => {
+                // a literal brace
+                template.push_str("{{ \"{\" }}");
+                rest = &rest[1..];
+            }
+        }
+    }
+    template.push_str(&rest.replace("}}", "}"));
+
+    Ok(template)
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade_legacy_formats() {
+        assert_eq!(
+            upgrade("➡️ {players} joined. ({count})").unwrap(),
+            "➡️ {{ players | names }} joined. ({{ count }})"
+        );
+        assert_eq!(
+            upgrade("{players_with_duration} left.").unwrap(),
+            "{{ players | names(with_duration=true) }} left."
+        );
+        assert_eq!(
+            upgrade("{count} {#} {").unwrap(),
+            "{{ count }} {{ \"{\" }}#} {{ \"{\" }}"
+        );
+        assert_eq!(upgrade("plain text").unwrap(), "plain text");
+        // `{{` and `}}` are literal braces in the legacy syntax
+        assert_eq!(
+            upgrade("{{beta}} {players} joined").unwrap(),
+            "{{ \"{\" }}beta} {{ players | names }} joined"
+        );
+
+        let template = "{% if count == 1 %}one{% else %}{{ count }}{% endif %}";
+        assert_eq!(upgrade(template).unwrap(), template);
+        assert_eq!(upgrade("{{ count }} joined").unwrap(), "{{ count }} joined");
+    }
+
+    #[test]
+    fn upgrade_rejects_format_specs() {
+        match upgrade("{count:>3} players") {
+            Err(reason) => assert!(reason.contains("`{count:>3}`")),
+            Ok(template) => panic!("expected an error: {}", template),
+        }
+        // a colon outside a placeholder is a literal
+        assert_eq!(upgrade("{a b:c}").unwrap(), "{{ \"{\" }}a b:c}");
+    }
+}
@@ -0,0 +1,39 @@
+use crate::models::{format_duration, Players, Sessions};
+use chrono::Duration;
+
+/// a player in the template context, e.g. `{% for player in players %}{{ player.name }}{% endfor %}`.
+#[derive(Serialize, Debug, PartialEq)]
+pub struct PlayerContext {
+    pub name: String,
+    pub id: String,
+    /// how long the player stayed, e.g. "2h13m". only for players who left.
+    pub duration: Option<String>,
+}
+
+impl PlayerContext {
+    /// sorted by names, the same as `Players` is displayed.
+    pub fn from_players(players: &Players) -> Vec<Self> {
+        Self::with_sessions(players, &Sessions::default(), format_duration)
+    }
+
+    /// fills the durations of the players from `sessions`, written by `format_duration`.
+    pub fn with_sessions<F>(players: &Players, sessions: &Sessions, format_duration: F) -> Vec<Self>
+    where
+        F: Fn(&Duration) -> String,
+    {
+        let mut players: Vec<Self> = players
+            .iter()
+            .map(|player| Self {
+                name: player.name().clone(),
+                id: player.id().clone(),
+                duration: sessions
+                    .iter()
+                    .find(|session| session.player().id() == player.id())
+                    .map(|session| format_duration(&session.duration())),
+            })
+            .collect();
+
+        players.sort_by(|lhs, rhs| lhs.name.cmp(&rhs.name));
+        players
+    }
+}
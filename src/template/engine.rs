@@ -0,0 +1,343 @@
+extern crate tera;
+
+use super::upgrade;
+use crate::locale::{Locale, Plural};
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error as StdError;
+use tera::ast::{Expr, ExprVal, FunctionCall, Node};
+use tera::{Context, Tera, Value};
+
+#[derive(Debug)]
+pub enum Error {
+    /// the template of the name couldn't be parsed.
+    InvalidTemplate(String, String),
+    RenderError(String, String),
+}
+
+/// the templates of the messages, rendered with tera.
+/// see `README.md` for the syntax and the variables.
+pub struct Templates {
+    tera: Tera,
+}
+
+impl Default for Templates {
+    fn default() -> Self {
+        Self::new(Locale::default())
+    }
+}
+
+impl Templates {
+    /// `locale` decides the plural forms of the `plural` filter.
+    pub fn new(locale: Locale) -> Self {
+        let mut tera = Tera::default();
+        // messages are not HTML
+        tera.autoescape_on(Vec::new());
+        tera.register_filter("names", names);
+        tera.register_filter(
+            "plural",
+            move |value: &Value, args: &HashMap<String, Value>| plural(locale, value, args),
+        );
+
+        Self { tera }
+    }
+
+    /// adds a template written either in the template syntax or in the legacy `{name}` format.
+    pub fn add(&mut self, name: &str, source: &str) -> Result<(), Error> {
+        let source = upgrade(source).map_err(|e| Error::InvalidTemplate(name.to_owned(), e))?;
+        self.tera
+            .add_raw_template(name, &source)
+            .map_err(|e| Error::InvalidTemplate(name.to_owned(), describe(&e)))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.tera.get_template_names().any(|n| n == name)
+    }
+
+    /// the names of the variables which the template reads from the context,
+    /// except the ones defined in the template such as loop variables.
+    pub fn variables(&self, name: &str) -> Vec<String> {
+        let template = match self.tera.templates.get(name) {
+            Some(template) => template,
+            None => return Vec::new(),
+        };
+
+        let mut variables = Variables::default();
+        variables.nodes(&template.ast);
+
+        variables
+            .used
+            .difference(&variables.defined)
+            .cloned()
+            .collect()
+    }
+
+    pub fn render(&self, name: &str, context: &Context) -> Result<String, Error> {
+        self.tera
+            .render(name, context)
+            .map_err(|e| Error::RenderError(name.to_owned(), describe(&e)))
+    }
+}
+
+/// collects the variables in a template.
+#[derive(Default)]
+struct Variables {
+    used: BTreeSet<String>,
+    defined: BTreeSet<String>,
+}
+
+impl Variables {
+    fn nodes(&mut self, nodes: &[Node]) {
+        for node in nodes {
+            self.node(node);
+        }
+    }
+
+    fn node(&mut self, node: &Node) {
+        match *node {
+            Node::VariableBlock(_, ref expr) => self.expr(expr),
+            Node::Set(_, ref set) => {
+                self.defined.insert(set.key.clone());
+                self.expr(&set.value);
+            }
+            Node::FilterSection(_, ref section, _) => {
+                self.call(&section.filter);
+                self.nodes(&section.body);
+            }
+            Node::Block(_, ref block, _) => self.nodes(&block.body),
+            Node::Forloop(_, ref forloop, _) => {
+                self.defined.insert("loop".to_owned());
+                self.defined.insert(forloop.value.clone());
+                if let Some(ref key) = forloop.key {
+                    self.defined.insert(key.clone());
+                }
+                self.expr(&forloop.container);
+                self.nodes(&forloop.body);
+                if let Some(ref body) = forloop.empty_body {
+                    self.nodes(body);
+                }
+            }
+            Node::If(ref condition, _) => {
+                for (_, ref expr, ref body) in condition.conditions.iter() {
+                    self.expr(expr);
+                    self.nodes(body);
+                }
+                if let Some((_, ref body)) = condition.otherwise {
+                    self.nodes(body);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        self.value(&expr.val);
+        for filter in expr.filters.iter() {
+            self.call(filter);
+        }
+    }
+
+    fn call(&mut self, call: &FunctionCall) {
+        for arg in call.args.values() {
+            self.expr(arg);
+        }
+    }
+
+    fn value(&mut self, value: &ExprVal) {
+        match *value {
+            ExprVal::Ident(ref ident) => self.ident(ident),
+            ExprVal::Math(ref math) => {
+                self.expr(&math.lhs);
+                self.expr(&math.rhs);
+            }
+            ExprVal::Logic(ref logic) => {
+                self.expr(&logic.lhs);
+                self.expr(&logic.rhs);
+            }
+            ExprVal::Test(ref test) => {
+                self.ident(&test.ident);
+                for arg in test.args.iter() {
+                    self.expr(arg);
+                }
+            }
+            ExprVal::FunctionCall(ref call) => self.call(call),
+            ExprVal::MacroCall(ref call) => {
+                for arg in call.args.values() {
+                    self.expr(arg);
+                }
+            }
+            ExprVal::Array(ref items) => {
+                for item in items.iter() {
+                    self.expr(item);
+                }
+            }
+            ExprVal::StringConcat(ref concat) => {
+                for value in concat.values.iter() {
+                    self.value(value);
+                }
+            }
+            ExprVal::In(ref r#in) => {
+                self.expr(&r#in.lhs);
+                self.expr(&r#in.rhs);
+            }
+            _ => {}
+        }
+    }
+
+    /// `player.name` and `players[0]` read `player` and `players`.
+    fn ident(&mut self, ident: &str) {
+        let root = ident
+            .split(|c| c == '.' || c == '[')
+            .next()
+            .unwrap_or(ident);
+        self.used.insert(root.to_owned());
+    }
+}
+
+/// tera puts the details into the sources of the error.
+fn describe(error: &tera::Error) -> String {
+    let mut description = error.to_string();
+    let mut source = error.source();
+
+    while let Some(e) = source {
+        description.push_str(": ");
+        description.push_str(&e.to_string());
+        source = e.source();
+    }
+
+    description
+}
+
+/// `players | names` joins the names of the players with commas,
+/// and `players | names(with_duration=true)` adds how long they stayed, e.g. "Alex (2h13m)".
+fn names(value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let players = value
+        .as_array()
+        .ok_or_else(|| tera::Error::msg("`names` expects a list of players"))?;
+    let with_duration = args
+        .get("with_duration")
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+
+    let names: Vec<String> = players
+        .iter()
+        .map(|player| {
+            let name = player
+                .get("name")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            match player.get("duration").and_then(Value::as_str) {
+                Some(duration) if with_duration => format!("{} ({})", name, duration),
+                _ => name.to_owned(),
+            }
+        })
+        .collect();
+
+    Ok(Value::String(names.join(", ")))
+}
+
+/// `count | plural(one="player", other="players")` chooses the form by the plural rules of the locale.
+/// `one` is optional.
+fn plural(locale: Locale, value: &Value, args: &HashMap<String, Value>) -> tera::Result<Value> {
+    let n = value
+        .as_i64()
+        .ok_or_else(|| tera::Error::msg("`plural` expects an integer"))?;
+    let other = args
+        .get("other")
+        .ok_or_else(|| tera::Error::msg("`plural` requires `other`"))?;
+
+    match locale.plural(n) {
+        Plural::One => Ok(args.get("one").unwrap_or(other).clone()),
+        Plural::Other => Ok(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{format_duration, Player, Players, Session, Sessions};
+    use crate::template::PlayerContext;
+
+    #[test]
+    fn templates_render_players() {
+        let mut templates = Templates::default();
+        templates
+            .add(
+                "players",
+                "{{ count }} player{{ count | pluralize }}: {% for p in players %}{{ p.name }}{% if not loop.last %} / {% endif %}{% endfor %}",
+            )
+            .unwrap();
+        templates
+            .add("legacy", "{players_with_duration} left.")
+            .unwrap();
+        assert!(templates.add("broken", "{{ count").is_err());
+        assert!(!templates.contains("broken"));
+
+        let players = Players::from(vec![Player::new("idB", "B"), Player::new("idA", "A")]);
+        let sessions = Sessions::from(vec![Session::new(
+            Player::new("idA", "A"),
+            chrono::Duration::minutes(5),
+        )]);
+
+        let mut context = Context::new();
+        context.insert("count", &1);
+        context.insert(
+            "players",
+            &PlayerContext::with_sessions(&players, &sessions, format_duration),
+        );
+
+        assert_eq!(
+            templates.render("players", &context).unwrap(),
+            "1 player: A / B"
+        );
+        assert_eq!(
+            templates.render("legacy", &context).unwrap(),
+            "A (5m), B left."
+        );
+
+        context.insert("count", &2);
+        assert!(templates
+            .render("players", &context)
+            .unwrap()
+            .starts_with("2 players"));
+    }
+
+    #[test]
+    fn templates_choose_plural_forms() {
+        let source = "{{ count }} {{ count | plural(one=\"player\", other=\"players\") }}";
+
+        let mut english = Templates::new(Locale::English);
+        english.add("count", source).unwrap();
+        let mut japanese = Templates::new(Locale::Japanese);
+        japanese.add("count", source).unwrap();
+
+        let mut context = Context::new();
+        for (count, english_message, japanese_message) in
+            [(1, "1 player", "1 players"), (2, "2 players", "2 players")]
+        {
+            context.insert("count", &count);
+            assert_eq!(english.render("count", &context).unwrap(), english_message);
+            assert_eq!(
+                japanese.render("count", &context).unwrap(),
+                japanese_message
+            );
+        }
+    }
+
+    #[test]
+    fn templates_list_variables() {
+        let mut templates = Templates::default();
+        templates
+            .add(
+                "message",
+                "{% set n = count + 1 %}{% for p in players | sort(attribute=key) %}{{ p.name }}{{ loop.index }}{% endfor %}{% if downtime and reason is defined %}{{ n }}{% endif %}",
+            )
+            .unwrap();
+        templates.add("legacy", "{players} {count}").unwrap();
+
+        assert_eq!(
+            templates.variables("message"),
+            vec!["count", "downtime", "key", "players", "reason"]
+        );
+        assert_eq!(templates.variables("legacy"), vec!["count", "players"]);
+    }
+}
@@ -0,0 +1,7 @@
+mod compat;
+mod context;
+mod engine;
+
+pub use self::compat::upgrade;
+pub use self::context::PlayerContext;
+pub use self::engine::{Error, Templates};
@@ -0,0 +1,71 @@
+extern crate chrono_tz;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use std::str::FromStr;
+
+/// the timezone in which schedules and time windows are evaluated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Timezone {
+    /// the timezone of the system
+    Local,
+    /// a timezone of the IANA database, e.g. `Asia/Tokyo`
+    Named(chrono_tz::Tz),
+}
+
+impl Default for Timezone {
+    fn default() -> Self {
+        Timezone::Local
+    }
+}
+
+impl FromStr for Timezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("local") {
+            return Ok(Timezone::Local);
+        }
+
+        s.parse().map(Timezone::Named)
+    }
+}
+
+impl Timezone {
+    pub fn to_local(&self, datetime: DateTime<Utc>) -> NaiveDateTime {
+        match *self {
+            Timezone::Local => datetime.with_timezone(&Local).naive_local(),
+            Timezone::Named(tz) => datetime.with_timezone(&tz).naive_local(),
+        }
+    }
+
+    /// returns `None` if the time doesn't exist because of the daylight saving time.
+    pub fn to_utc(&self, datetime: &NaiveDateTime) -> Option<DateTime<Utc>> {
+        match *self {
+            Timezone::Local => Local
+                .from_local_datetime(datetime)
+                .earliest()
+                .map(|datetime| datetime.with_timezone(&Utc)),
+            Timezone::Named(tz) => tz
+                .from_local_datetime(datetime)
+                .earliest()
+                .map(|datetime| datetime.with_timezone(&Utc)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    #[test]
+    fn timezone_converts_named_timezones() {
+        let timezone: Timezone = "Asia/Tokyo".parse().unwrap();
+        let utc = Utc.ymd(2022, 7, 1).and_hms(15, 0, 0);
+        let local = NaiveDate::from_ymd(2022, 7, 2).and_hms(0, 0, 0);
+
+        assert_eq!(timezone.to_local(utc), local);
+        assert_eq!(timezone.to_utc(&local), Some(utc));
+        assert!("Mars/Olympus_Mons".parse::<Timezone>().is_err());
+    }
+}